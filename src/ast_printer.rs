@@ -56,8 +56,31 @@ impl Visitor for AstPrinter {
         return self.parenthesize(&logical.operator.lexeme, &vec![&logical.left, &logical.right]);
     }
 
+    // A piped call shows what it desugars to: `x |> f(y)` is `(|> f x y)`.
     fn visit_call(&mut self, call: &Call) -> Self::Output {
-        return self.parenthesize(&"function".to_owned(), &call.arguments.iter().collect());
+        if call.piped {
+            let mut exprs = vec![call.callee.as_ref()];
+            exprs.extend(call.arguments.iter());
+            return self.parenthesize(if call.optional { "?.|>" } else { "|>" }, &exprs);
+        }
+        let name = if call.optional { "?.function" } else { "function" };
+        return self.parenthesize(name, &call.arguments.iter().collect());
+    }
+
+    fn visit_get(&mut self, get: &Get) -> Self::Output {
+        let operator = if get.optional { "?." } else { "." };
+        return format!("({} {} {})", operator, get.object.accept(self), get.name.lexeme);
+    }
+
+    fn visit_index(&mut self, index: &Index) -> Self::Output {
+        return self.parenthesize("index", &vec![&index.object, &index.index]);
+    }
+
+    fn visit_tuple(&mut self, tuple: &Tuple) -> Self::Output {
+        return self.parenthesize("tuple", &tuple.elements.iter().collect());
     }
-}
 
+    fn visit_range(&mut self, range: &Range) -> Self::Output {
+        return self.parenthesize(&range.operator.lexeme, &vec![&range.start, &range.end]);
+    }
+}
@@ -0,0 +1,180 @@
+use bytes::{Bytes, BytesMut};
+use crate::interpreter::{Interpreter, RuntimeException};
+use crate::native::{check_string, error, NativeResult};
+use crate::token::{LiteralType, Token};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("slice", 3, slice);
+    interpreter.register_native("encodeUtf8", 1, encode_utf8);
+    interpreter.register_native("decodeUtf8", 1, decode_utf8);
+    interpreter.register_native("hex", 1, hex);
+    interpreter.register_native("fromHex", 1, from_hex);
+    interpreter.register_native("base64", 1, base64);
+    interpreter.register_native("fromBase64", 1, from_base64);
+}
+
+// Formats bytes the way they are written in source, e.g. b"GET \x00\n".
+pub fn repr(bytes: &Bytes) -> String {
+    let mut result = String::from("b\"");
+    for byte in bytes.iter() {
+        match byte {
+            b'"' => result.push_str("\\\""),
+            b'\\' => result.push_str("\\\\"),
+            b'\n' => result.push_str("\\n"),
+            b'\r' => result.push_str("\\r"),
+            b'\t' => result.push_str("\\t"),
+            0x20..=0x7e => result.push(*byte as char),
+            _ => result.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    result.push('"');
+    return result;
+}
+
+pub fn concat(left: &Bytes, right: &Bytes) -> Bytes {
+    let mut result = BytesMut::with_capacity(left.len() + right.len());
+    result.extend_from_slice(left);
+    result.extend_from_slice(right);
+    return result.freeze();
+}
+
+pub fn hex_digit_value(c: char) -> Option<u8> {
+    c.to_digit(16).map(|d| d as u8)
+}
+
+fn hex_encode(bytes: &Bytes) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for byte in bytes.iter() {
+        result.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        result.push(HEX_DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    return result;
+}
+
+// `digits` must have an even length. The first character that isn't a hex
+// digit is returned as the error.
+fn hex_decode(digits: &[char]) -> Result<Bytes, char> {
+    let value = |c: char| hex_digit_value(c).ok_or(c);
+    let mut result = BytesMut::with_capacity(digits.len() / 2);
+    for pair in digits.chunks(2) {
+        let high = value(pair[0])?;
+        let low = value(pair[1])?;
+        result.extend_from_slice(&[(high << 4) | low]);
+    }
+    return Ok(result.freeze());
+}
+
+fn base64_encode(bytes: &Bytes) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                result.push(BASE64_ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    return result;
+}
+
+fn base64_decode(text: &str) -> Option<Bytes> {
+    let text = text.as_bytes();
+    if text.len() % 4 != 0 {
+        return None;
+    }
+
+    let mut result = BytesMut::with_capacity(text.len() / 4 * 3);
+    for (index, chunk) in text.chunks(4).enumerate() {
+        let is_last = index == text.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return None;
+        }
+
+        let mut group: u32 = 0;
+        for c in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET.iter().position(|a| a == c)?;
+            group = group << 6 | value as u32;
+        }
+        group <<= 6 * padding;
+
+        let decoded = [(group >> 16) as u8, (group >> 8) as u8, group as u8];
+        result.extend_from_slice(&decoded[..3 - padding]);
+    }
+    return Some(result.freeze());
+}
+
+fn check_bytes(paren: &Token, function: &str, value: &Option<LiteralType>) -> Result<Bytes, RuntimeException> {
+    match value {
+        Some(LiteralType::Bytes(bytes)) => return Ok(bytes.clone()),
+        _ => return Err(error(paren, &format!("{}: argument must be bytes.", function))),
+    }
+}
+
+fn check_index(paren: &Token, function: &str, value: &Option<LiteralType>, length: usize) -> Result<usize, RuntimeException> {
+    match value {
+        Some(LiteralType::Number(n)) if n.fract() == 0.0 && *n >= 0.0 && *n <= length as f64 => return Ok(*n as usize),
+        _ => return Err(error(paren, &format!("{}: index must be an integer between 0 and {}.", function, length))),
+    }
+}
+
+pub fn slice(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let bytes = check_bytes(paren, "slice", &arguments[0])?;
+    let start = check_index(paren, "slice", &arguments[1], bytes.len())?;
+    let end = check_index(paren, "slice", &arguments[2], bytes.len())?;
+    if start > end {
+        return Err(error(paren, "slice: start must not be after end."));
+    }
+    return Ok(Some(LiteralType::Bytes(bytes.slice(start..end))));
+}
+
+pub fn encode_utf8(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "encodeUtf8", "argument", &arguments[0])?;
+    return Ok(Some(LiteralType::Bytes(Bytes::from(string))));
+}
+
+pub fn decode_utf8(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let bytes = check_bytes(paren, "decodeUtf8", &arguments[0])?;
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(string) => return Ok(Some(LiteralType::String(string))),
+        Err(e) => return Err(error(paren, &format!("decodeUtf8: {}.", e))),
+    }
+}
+
+pub fn hex(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let bytes = check_bytes(paren, "hex", &arguments[0])?;
+    return Ok(Some(LiteralType::String(hex_encode(&bytes))));
+}
+
+pub fn from_hex(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let text = check_string(paren, "fromHex", "argument", &arguments[0])?;
+    let digits: Vec<char> = text.chars().collect();
+    if digits.len() % 2 != 0 {
+        return Err(error(paren, "fromHex: argument is not an even number of hex digits."));
+    }
+    match hex_decode(&digits) {
+        Ok(bytes) => return Ok(Some(LiteralType::Bytes(bytes))),
+        Err(c) => return Err(error(paren, &format!("fromHex: '{}' is not a hex digit.", c))),
+    }
+}
+
+pub fn base64(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let bytes = check_bytes(paren, "base64", &arguments[0])?;
+    return Ok(Some(LiteralType::String(base64_encode(&bytes))));
+}
+
+pub fn from_base64(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let text = check_string(paren, "fromBase64", "argument", &arguments[0])?;
+    match base64_decode(&text) {
+        Some(bytes) => return Ok(Some(LiteralType::Bytes(bytes))),
+        None => return Err(error(paren, "fromBase64: argument is not valid base64.")),
+    }
+}
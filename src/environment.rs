@@ -1,10 +1,14 @@
 use std::{collections::HashMap, cell::RefCell, rc::Rc};
-use crate::{interpreter::{RuntimeException, RuntimeError}, token::{LiteralType, Token}};
+use crate::{interpreter::{RuntimeException, RuntimeError}, statement::Statement, token::{LiteralType, Token}};
 
 #[derive(Debug, PartialEq)]
 pub struct Environment {
     values: RefCell<HashMap<String, Option<LiteralType>>>,
     pub enclosing: Option<Rc<Environment>>,
+    // Statements registered with `defer` in this scope, in registration order.
+    deferred: RefCell<Vec<Rc<Statement>>>,
+    // Whether this is the scope a function call binds its parameters in.
+    call: bool,
 }
 
 impl Environment {
@@ -12,6 +16,35 @@ impl Environment {
         Self {
             values: RefCell::new(HashMap::new()),
             enclosing: enclosing.map(|e| Rc::clone(e)),
+            deferred: RefCell::new(Vec::new()),
+            call: false,
+        }
+    }
+
+    pub fn new_call(closure: &Rc<Environment>) -> Self {
+        Self {
+            call: true,
+            ..Self::new(Some(closure))
+        }
+    }
+
+    pub fn defer(&self, statement: Rc<Statement>) {
+        self.deferred.borrow_mut().push(statement);
+    }
+
+    pub fn take_deferred(&self) -> Vec<Rc<Statement>> {
+        self.deferred.take()
+    }
+
+    // Whether anything is deferred in this scope or the ones around it, out
+    // to the innermost function call's, or the script's outside of any.
+    pub fn call_has_deferred(&self) -> bool {
+        if !self.deferred.borrow().is_empty() {
+            return true;
+        }
+        match &self.enclosing {
+            Some(enclosing) if !self.call => return enclosing.call_has_deferred(),
+            _ => return false,
         }
     }
 
@@ -106,14 +106,91 @@ pub struct Call {
     pub callee: Box<Expr>,
     pub paren: Token,
     pub arguments: Vec<Expr>,
+    // `f?.(x)`: evaluates to nil, without evaluating the arguments, when the callee is nil.
+    pub optional: bool,
+    // Written as `x |> f`, which only matters to the AST printer.
+    pub piped: bool,
 }
 
 impl Call {
-    pub fn new(callee: Expr, paren: Token, arguments: Vec<Expr>) -> Self {
+    pub fn new(callee: Expr, paren: Token, arguments: Vec<Expr>, optional: bool) -> Self {
         Self {
             callee: Box::new(callee),
             paren,
             arguments,
+            optional,
+            piped: false,
+        }
+    }
+
+    pub fn piped(mut self) -> Self {
+        self.piped = true;
+        return self;
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Get {
+    pub object: Box<Expr>,
+    pub name: Token,
+    // `obj?.name`: evaluates to nil when the object is nil.
+    pub optional: bool,
+}
+
+impl Get {
+    pub fn new(object: Expr, name: Token, optional: bool) -> Self {
+        Self {
+            object: Box::new(object),
+            name,
+            optional,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Index {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+impl Index {
+    pub fn new(object: Expr, bracket: Token, index: Expr) -> Self {
+        Self {
+            object: Box::new(object),
+            bracket,
+            index: Box::new(index),
+        }
+    }
+}
+
+// `(a, b, c)`. Unlike a grouping it has zero or at least two elements, or a
+// trailing comma: `(a,)`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Tuple {
+    pub elements: Vec<Expr>,
+}
+
+impl Tuple {
+    pub fn new(elements: Vec<Expr>) -> Self {
+        Self { elements }
+    }
+}
+
+// `start..end`, or `start..=end` when the operator is DotDotEqual.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Range {
+    pub start: Box<Expr>,
+    pub operator: Token,
+    pub end: Box<Expr>,
+}
+
+impl Range {
+    pub fn new(start: Expr, operator: Token, end: Expr) -> Self {
+        Self {
+            start: Box::new(start),
+            operator,
+            end: Box::new(end),
         }
     }
 }
@@ -128,6 +205,10 @@ pub enum Expr {
     Assign(Assign),
     Logical(Logical),
     Call(Call),
+    Get(Get),
+    Index(Index),
+    Tuple(Tuple),
+    Range(Range),
 }
 
 impl Expr {
@@ -141,6 +222,10 @@ impl Expr {
             Expr::Assign(assign) => visitor.visit_assign(assign),
             Expr::Logical(logical) => visitor.visit_logical(logical),
             Expr::Call(call) => visitor.visit_call(call),
+            Expr::Get(get) => visitor.visit_get(get),
+            Expr::Index(index) => visitor.visit_index(index),
+            Expr::Tuple(tuple) => visitor.visit_tuple(tuple),
+            Expr::Range(range) => visitor.visit_range(range),
         };
     }
 }
@@ -155,4 +240,8 @@ pub trait Visitor {
     fn visit_assign(&mut self, assign: &Assign) -> Self::Output;
     fn visit_logical(&mut self, logical: &Logical) -> Self::Output;
     fn visit_call(&mut self, call: &Call) -> Self::Output;
+    fn visit_get(&mut self, get: &Get) -> Self::Output;
+    fn visit_index(&mut self, index: &Index) -> Self::Output;
+    fn visit_tuple(&mut self, tuple: &Tuple) -> Self::Output;
+    fn visit_range(&mut self, range: &Range) -> Self::Output;
 }
\ No newline at end of file
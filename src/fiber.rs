@@ -0,0 +1,292 @@
+// Cooperative fibers and channels.
+//
+// Every spawned fiber runs on its own stack and only ever switches back to the
+// main script, which picks the next fiber to run. Fibers are run round-robin
+// in the order they became ready, so a program always interleaves them the
+// same way.
+use std::{cell::RefCell, collections::{HashMap, VecDeque}, fmt::Display, ptr, rc::Rc};
+use corosensei::{stack::DefaultStack, Coroutine, CoroutineResult, Yielder};
+use crate::interpreter::{Interpreter, RuntimeError, RuntimeException};
+use crate::lox_callable::LoxCallable;
+use crate::token::{LiteralType, Token};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("spawn", 1, spawn);
+    interpreter.register_reentrant_native("yield", 0, yield_fiber);
+    interpreter.register_native("channel", 0, channel);
+    interpreter.register_native("send", 2, send);
+    interpreter.register_reentrant_native("receive", 1, receive);
+    interpreter.register_native("close", 1, close);
+}
+
+const MAIN_FIBER: FiberId = 0;
+const FIBER_STACK_SIZE: usize = 8 * 1024 * 1024;
+const DEADLOCK: &str = "receive: every fiber is blocked, nothing can send to this channel.";
+
+type FiberId = usize;
+type FiberYielder = Yielder<*mut Interpreter, ()>;
+type Fiber = Coroutine<*mut Interpreter, (), Result<(), RuntimeException>>;
+
+#[derive(Debug)]
+struct ChannelState {
+    values: VecDeque<Option<LiteralType>>,
+    waiting: VecDeque<FiberId>,
+    closed: bool,
+}
+
+// An unbounded FIFO queue of values. Sending never blocks; receiving from an
+// empty channel blocks the fiber until another one sends, or gives nil once
+// the channel is closed.
+#[derive(Clone, Debug)]
+pub struct Channel {
+    state: Rc<RefCell<ChannelState>>,
+}
+
+impl Channel {
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(ChannelState {
+                values: VecDeque::new(),
+                waiting: VecDeque::new(),
+                closed: false,
+            })),
+        }
+    }
+}
+
+impl PartialEq for Channel {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<channel>")
+    }
+}
+
+pub struct Scheduler {
+    fibers: HashMap<FiberId, Fiber>,
+    ready: VecDeque<FiberId>,
+    current: FiberId,
+    next_id: FiberId,
+    // Lets the running fiber suspend itself; null while the main script runs.
+    yielder: *const FiberYielder,
+    // Where each fiber waiting in `receive` is waiting, for reporting them.
+    blocked: HashMap<FiberId, Token>,
+    // Set while `cancel_fibers` unwinds what is left.
+    cancelling: bool,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            fibers: HashMap::new(),
+            ready: VecDeque::new(),
+            current: MAIN_FIBER,
+            next_id: MAIN_FIBER + 1,
+            yielder: ptr::null(),
+            blocked: HashMap::new(),
+            cancelling: false,
+        }
+    }
+}
+
+// Runs ready fibers, one after another, until the main script is ready again.
+// Returns false if the fibers all finished or blocked before that happened.
+// Only ever called from the main script.
+pub fn run_fibers(interpreter: &mut Interpreter) -> Result<bool, RuntimeException> {
+    loop {
+        let id = match interpreter.scheduler().ready.pop_front() {
+            None => return Ok(false),
+            Some(MAIN_FIBER) => return Ok(true),
+            Some(id) => id,
+        };
+
+        let mut fiber = interpreter.scheduler().fibers.remove(&id).unwrap();
+        match resume(interpreter, id, &mut fiber) {
+            CoroutineResult::Yield(()) => { interpreter.scheduler().fibers.insert(id, fiber); }
+            CoroutineResult::Return(result) => result?,
+        }
+    }
+}
+
+// Fibers still waiting to receive once the script and every ready fiber are
+// done are deadlocked; the first one spawned is reported.
+pub fn check_blocked(interpreter: &mut Interpreter) -> Result<(), RuntimeException> {
+    let blocked = &interpreter.scheduler().blocked;
+    match blocked.keys().min().map(|id| &blocked[id]) {
+        Some(paren) => return Err(RuntimeException::RuntimeError(RuntimeError::new(paren, DEADLOCK))),
+        None => return Ok(()),
+    }
+}
+
+// Unwinds every fiber left suspended when the script ends, blocked or cut
+// short by an error elsewhere, so their deferred statements still run. Each
+// switch they try fails the way `exit` does, and how they end is ignored.
+// Fibers that never started are just dropped.
+pub fn cancel_fibers(interpreter: &mut Interpreter) {
+    interpreter.scheduler().ready.clear();
+    interpreter.scheduler().cancelling = true;
+    while let Some(&id) = interpreter.scheduler().fibers.keys().min() {
+        let mut fiber = interpreter.scheduler().fibers.remove(&id).unwrap();
+        if fiber.started() {
+            let _ = resume(interpreter, id, &mut fiber);
+        }
+    }
+    interpreter.scheduler().cancelling = false;
+    interpreter.scheduler().ready.clear();
+    interpreter.scheduler().blocked.clear();
+}
+
+// Runs fiber `id` until it next switches away or ends, then restores the
+// main script's state.
+fn resume(interpreter: &mut Interpreter, id: FiberId, fiber: &mut Fiber) -> CoroutineResult<(), Result<(), RuntimeException>> {
+    let environment = interpreter.environment();
+    let stack_limit = interpreter.stack_limit();
+
+    interpreter.scheduler().current = id;
+    let result = fiber.resume(interpreter as *mut Interpreter);
+    interpreter.scheduler().current = MAIN_FIBER;
+    interpreter.scheduler().yielder = ptr::null();
+    interpreter.set_environment(environment);
+    interpreter.set_stack_limit(stack_limit);
+
+    return result;
+}
+
+// Gives up the processor. With `requeue` the current fiber goes to the back of
+// the ready queue, otherwise it sleeps until something makes it ready again.
+fn switch(interpreter: &mut Interpreter, requeue: bool) -> Result<bool, RuntimeException> {
+    if interpreter.scheduler().cancelling {
+        return Err(RuntimeException::Exit(0));
+    }
+    let current = interpreter.scheduler().current;
+    if requeue {
+        interpreter.scheduler().ready.push_back(current);
+    }
+
+    if current == MAIN_FIBER {
+        return run_fibers(interpreter);
+    }
+
+    let yielder = interpreter.scheduler().yielder;
+    let environment = interpreter.environment();
+    let stack_limit = interpreter.stack_limit();
+
+    // The main script hands the interpreter back each time it resumes this
+    // fiber; the old reference must not be used after suspending.
+    let interpreter = unsafe { &mut *(*yielder).suspend(()) };
+    interpreter.scheduler().yielder = yielder;
+    interpreter.set_environment(environment);
+    interpreter.set_stack_limit(stack_limit);
+
+    if interpreter.scheduler().cancelling {
+        return Err(RuntimeException::Exit(0));
+    }
+    return Ok(true);
+}
+
+pub fn spawn(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> Result<Option<LiteralType>, RuntimeException> {
+    let mut function = match &arguments[0] {
+        Some(LiteralType::LoxCallable(function)) if function.arity() == 0 => function.clone(),
+        _ => {
+            return Err(RuntimeException::RuntimeError(RuntimeError::new(paren, "spawn: argument must be a function that takes no arguments.")));
+        }
+    };
+
+    let stack = match DefaultStack::new(FIBER_STACK_SIZE) {
+        Ok(stack) => stack,
+        Err(error) => {
+            return Err(RuntimeException::RuntimeError(RuntimeError::new(paren, &format!("spawn: could not allocate a fiber stack: {}", error))));
+        }
+    };
+
+    let spawned_at = paren.clone();
+    let fiber: Fiber = Coroutine::with_stack(stack, move |yielder: &FiberYielder, interpreter: *mut Interpreter| {
+        let interpreter = unsafe { &mut *interpreter };
+        interpreter.scheduler().yielder = yielder;
+        interpreter.set_stack_size(FIBER_STACK_SIZE);
+        return function.call(interpreter, &spawned_at, Vec::new()).map(|_| ());
+    });
+
+    let scheduler = interpreter.scheduler();
+    let id = scheduler.next_id;
+    scheduler.next_id += 1;
+    scheduler.fibers.insert(id, fiber);
+    scheduler.ready.push_back(id);
+
+    return Ok(None);
+}
+
+pub fn yield_fiber(interpreter: &mut Interpreter, _paren: &Token, _arguments: Vec<Option<LiteralType>>) -> Result<Option<LiteralType>, RuntimeException> {
+    switch(interpreter, true)?;
+    return Ok(None);
+}
+
+pub fn channel(_interpreter: &mut Interpreter, _paren: &Token, _arguments: Vec<Option<LiteralType>>) -> Result<Option<LiteralType>, RuntimeException> {
+    return Ok(Some(LiteralType::Channel(Channel::new())));
+}
+
+pub fn send(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> Result<Option<LiteralType>, RuntimeException> {
+    let channel = check_channel(paren, "send", &arguments[0])?;
+
+    let mut state = channel.state.borrow_mut();
+    if state.closed {
+        return Err(RuntimeException::RuntimeError(RuntimeError::new(paren, "send: channel is closed.")));
+    }
+    state.values.push_back(arguments[1].clone());
+    if let Some(id) = state.waiting.pop_front() {
+        interpreter.scheduler().ready.push_back(id);
+    }
+
+    return Ok(None);
+}
+
+pub fn receive(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> Result<Option<LiteralType>, RuntimeException> {
+    let channel = check_channel(paren, "receive", &arguments[0])?;
+
+    loop {
+        let current = interpreter.scheduler().current;
+        {
+            let mut state = channel.state.borrow_mut();
+            if let Some(value) = state.values.pop_front() {
+                return Ok(value);
+            }
+            if state.closed {
+                return Ok(None);
+            }
+            state.waiting.push_back(current);
+        }
+
+        interpreter.scheduler().blocked.insert(current, paren.clone());
+        let switched = switch(interpreter, false);
+        interpreter.scheduler().blocked.remove(&current);
+        if !switched? {
+            return Err(RuntimeException::RuntimeError(RuntimeError::new(paren, DEADLOCK)));
+        }
+    }
+}
+
+// Values already sent can still be received; after them every fiber waiting
+// on the channel gets nil.
+pub fn close(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> Result<Option<LiteralType>, RuntimeException> {
+    let channel = check_channel(paren, "close", &arguments[0])?;
+
+    let mut state = channel.state.borrow_mut();
+    if state.closed {
+        return Err(RuntimeException::RuntimeError(RuntimeError::new(paren, "close: channel is already closed.")));
+    }
+    state.closed = true;
+    interpreter.scheduler().ready.extend(state.waiting.drain(..));
+
+    return Ok(None);
+}
+
+fn check_channel(paren: &Token, function: &str, value: &Option<LiteralType>) -> Result<Channel, RuntimeException> {
+    match value {
+        Some(LiteralType::Channel(channel)) => return Ok(channel.clone()),
+        _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(paren, &format!("{}: argument must be a channel.", function)))),
+    }
+}
@@ -0,0 +1,114 @@
+// File system natives. Every I/O failure becomes a runtime error naming the
+// native and the path, followed by the operating system's description.
+use std::{fs, io, path::Path, rc::Rc};
+use bytes::Bytes;
+use crate::interpreter::{Interpreter, RuntimeException};
+use crate::native::{check_sandbox, check_string, error, NativeResult};
+use crate::token::{LiteralType, Token};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("readFile", 1, read_file);
+    interpreter.register_native("writeFile", 2, write_file);
+    interpreter.register_native("appendFile", 2, append_file);
+    interpreter.register_native("readLines", 1, read_lines);
+    interpreter.register_native("exists", 1, exists);
+    interpreter.register_native("listDir", 1, list_dir);
+    interpreter.register_native("remove", 1, remove);
+    interpreter.register_native("fileSize", 1, file_size);
+}
+
+fn io_error(paren: &Token, function: &str, path: &str, e: io::Error) -> RuntimeException {
+    error(paren, &format!("{}: {}: {}.", function, path, e))
+}
+
+// What gets written: strings as UTF-8, byte strings as they are.
+fn check_contents(paren: &Token, function: &str, value: &Option<LiteralType>) -> Result<Bytes, RuntimeException> {
+    match value {
+        Some(LiteralType::String(string)) => return Ok(Bytes::from(string.clone())),
+        Some(LiteralType::Bytes(bytes)) => return Ok(bytes.clone()),
+        _ => return Err(error(paren, &format!("{}: contents must be a string or byte string.", function))),
+    }
+}
+
+pub fn read_file(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let path = check_string(paren, "readFile", "path", &arguments[0])?;
+    match fs::read_to_string(&path) {
+        Ok(contents) => return Ok(Some(LiteralType::String(contents))),
+        Err(e) => return Err(io_error(paren, "readFile", &path, e)),
+    }
+}
+
+pub fn write_file(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    check_sandbox(interpreter, paren, "writeFile")?;
+    let path = check_string(paren, "writeFile", "path", &arguments[0])?;
+    let contents = check_contents(paren, "writeFile", &arguments[1])?;
+    match fs::write(&path, contents) {
+        Ok(()) => return Ok(None),
+        Err(e) => return Err(io_error(paren, "writeFile", &path, e)),
+    }
+}
+
+pub fn append_file(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    check_sandbox(interpreter, paren, "appendFile")?;
+    let path = check_string(paren, "appendFile", "path", &arguments[0])?;
+    let contents = check_contents(paren, "appendFile", &arguments[1])?;
+
+    let result = fs::OpenOptions::new().append(true).create(true).open(&path)
+        .and_then(|mut file| io::Write::write_all(&mut file, &contents));
+    match result {
+        Ok(()) => return Ok(None),
+        Err(e) => return Err(io_error(paren, "appendFile", &path, e)),
+    }
+}
+
+pub fn read_lines(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let path = check_string(paren, "readLines", "path", &arguments[0])?;
+    match fs::read_to_string(&path) {
+        Ok(contents) => {
+            let lines = contents.lines().map(|line| Some(LiteralType::String(line.to_owned()))).collect();
+            return Ok(Some(LiteralType::Tuple(Rc::new(lines))));
+        }
+        Err(e) => return Err(io_error(paren, "readLines", &path, e)),
+    }
+}
+
+pub fn exists(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let path = check_string(paren, "exists", "path", &arguments[0])?;
+    return Ok(Some(LiteralType::Boolean(Path::new(&path).exists())));
+}
+
+// Entry names, sorted so that scripts behave the same on every platform.
+pub fn list_dir(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let path = check_string(paren, "listDir", "path", &arguments[0])?;
+
+    let entries = fs::read_dir(&path)
+        .and_then(|entries| entries.map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned())).collect::<io::Result<Vec<String>>>());
+    match entries {
+        Ok(mut names) => {
+            names.sort();
+            let names = names.into_iter().map(|name| Some(LiteralType::String(name))).collect();
+            return Ok(Some(LiteralType::Tuple(Rc::new(names))));
+        }
+        Err(e) => return Err(io_error(paren, "listDir", &path, e)),
+    }
+}
+
+// Removes a file or an empty directory.
+pub fn remove(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    check_sandbox(interpreter, paren, "remove")?;
+    let path = check_string(paren, "remove", "path", &arguments[0])?;
+
+    let result = if Path::new(&path).is_dir() { fs::remove_dir(&path) } else { fs::remove_file(&path) };
+    match result {
+        Ok(()) => return Ok(None),
+        Err(e) => return Err(io_error(paren, "remove", &path, e)),
+    }
+}
+
+pub fn file_size(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let path = check_string(paren, "fileSize", "path", &arguments[0])?;
+    match fs::metadata(&path) {
+        Ok(metadata) => return Ok(Some(LiteralType::Number(metadata.len() as f64))),
+        Err(e) => return Err(io_error(paren, "fileSize", &path, e)),
+    }
+}
@@ -0,0 +1,319 @@
+// `format(pattern, values...)`, filling `{}` placeholders in the pattern with
+// the values in order. A placeholder can carry a spec after a colon, much
+// like Python's and Rust's:
+//
+//   {:[[fill]align][sign][#][0][width][.precision][type]}
+//
+//   align      `<` left, `>` right or `^` centre, padding with `fill`
+//              (default a space); numbers go right by default, the rest left
+//   sign       `+` to show a sign on positive numbers too
+//   #          prefixes `0x`, `0X`, `0b` or `0o` to the integer types
+//   0          pads numbers with zeros after their sign and prefix
+//   width      the minimum number of characters
+//   precision  digits after the decimal point for numbers, and the maximum
+//              number of characters for anything else
+//   type       `x`/`X` hexadecimal, `b` binary or `o` octal, for integers
+//
+// Width and precision go up to MAX_WIDTH; a placeholder with a bigger one is
+// invalid. `{{` and `}}` stand for literal braces.
+use crate::interpreter::Interpreter;
+use crate::native::{check_string, error, NativeResult};
+use crate::token::{LiteralType, Token};
+
+// Rust's own formatting panics on precisions past this, and a width past it
+// would more likely be a runaway number than padding anyone wants.
+const MAX_WIDTH: usize = u16::MAX as usize;
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_variadic_native("format", 1, format);
+}
+
+pub fn format(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let pattern = check_string(paren, "format", "pattern", &arguments[0])?;
+    let values = &arguments[1..];
+
+    let pieces = match parse_pattern(&pattern) {
+        Ok(pieces) => pieces,
+        Err(message) => return Err(error(paren, &format!("format: {}.", message))),
+    };
+
+    let placeholders = pieces.iter().filter(|piece| matches!(piece, Piece::Placeholder(..))).count();
+    if placeholders != values.len() {
+        return Err(error(paren, &format!(
+            "format: pattern has {} placeholder{} but {} value{} {} given.",
+            placeholders,
+            if placeholders == 1 { "" } else { "s" },
+            values.len(),
+            if values.len() == 1 { "" } else { "s" },
+            if values.len() == 1 { "was" } else { "were" },
+        )));
+    }
+
+    let mut result = String::new();
+    let mut values = values.iter();
+    for piece in &pieces {
+        match piece {
+            Piece::Text(text) => result.push_str(text),
+            Piece::Placeholder(placeholder, spec) => {
+                match spec.render(interpreter, values.next().unwrap()) {
+                    Ok(text) => result.push_str(&text),
+                    Err(message) => return Err(error(paren, &format!("format: {} {}.", placeholder, message))),
+                }
+            }
+        }
+    }
+    return Ok(Some(LiteralType::String(result)));
+}
+
+enum Piece {
+    Text(String),
+    // The placeholder as written, for error messages, and its parsed spec.
+    Placeholder(String, Spec),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Display,
+    Hex,
+    UpperHex,
+    Binary,
+    Octal,
+}
+
+struct Spec {
+    fill: char,
+    align: Option<Align>,
+    sign: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    kind: Kind,
+}
+
+fn parse_pattern(pattern: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '}' => return Err("unmatched '}' in pattern; write '}}' for a literal brace".to_owned()),
+            '{' => {
+                let mut inside = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inside.push(c),
+                        None => return Err("unclosed '{' in pattern".to_owned()),
+                    }
+                }
+
+                let placeholder = format!("{{{}}}", inside);
+                let spec = match inside.strip_prefix(':') {
+                    _ if inside.is_empty() => Some(Spec::default()),
+                    Some(spec) => Spec::parse(spec),
+                    None => None,
+                };
+                match spec {
+                    Some(spec) => {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                        pieces.push(Piece::Placeholder(placeholder, spec));
+                    }
+                    None => return Err(format!("invalid placeholder {}", placeholder)),
+                }
+            }
+            c => text.push(c),
+        }
+    }
+
+    pieces.push(Piece::Text(text));
+    return Ok(pieces);
+}
+
+impl Spec {
+    fn default() -> Self {
+        Self {
+            fill: ' ',
+            align: None,
+            sign: false,
+            alternate: false,
+            zero: false,
+            width: 0,
+            precision: None,
+            kind: Kind::Display,
+        }
+    }
+
+    // The part after the colon, or None if it isn't a valid spec.
+    fn parse(spec: &str) -> Option<Self> {
+        let chars: Vec<char> = spec.chars().collect();
+        let mut result = Self::default();
+        let mut i = 0;
+
+        let align = |c: Option<&char>| match c {
+            Some('<') => Some(Align::Left),
+            Some('>') => Some(Align::Right),
+            Some('^') => Some(Align::Center),
+            _ => None,
+        };
+        if let Some(alignment) = align(chars.get(1)) {
+            result.fill = chars[0];
+            result.align = Some(alignment);
+            i = 2;
+        } else if let Some(alignment) = align(chars.first()) {
+            result.align = Some(alignment);
+            i = 1;
+        }
+
+        match chars.get(i) {
+            Some('+') => {
+                result.sign = true;
+                i += 1;
+            }
+            Some('-') => i += 1,
+            _ => {}
+        }
+        if chars.get(i) == Some(&'#') {
+            result.alternate = true;
+            i += 1;
+        }
+        if chars.get(i) == Some(&'0') {
+            result.zero = true;
+            i += 1;
+        }
+
+        let (width, next) = digits(&chars, i)?;
+        result.width = width.unwrap_or(0);
+        i = next;
+
+        if chars.get(i) == Some(&'.') {
+            let (precision, next) = digits(&chars, i + 1)?;
+            result.precision = Some(precision?);
+            i = next;
+        }
+
+        result.kind = match chars.get(i) {
+            None => Kind::Display,
+            Some('x') => Kind::Hex,
+            Some('X') => Kind::UpperHex,
+            Some('b') => Kind::Binary,
+            Some('o') => Kind::Octal,
+            Some(_) => return None,
+        };
+        if result.kind != Kind::Display {
+            i += 1;
+        }
+
+        if i != chars.len() {
+            return None;
+        }
+        return Some(result);
+    }
+
+    // The error message says what was wrong with the value for this spec.
+    fn render(&self, interpreter: &Interpreter, value: &Option<LiteralType>) -> Result<String, String> {
+        let number = match value {
+            Some(LiteralType::Number(n)) => Some(*n),
+            _ => None,
+        };
+
+        let (sign, prefix, body) = match (self.kind, number) {
+            (Kind::Display, Some(n)) => {
+                if self.alternate {
+                    return Err("can't use '#' without x, X, b or o".to_owned());
+                }
+                let text = match self.precision {
+                    Some(precision) => format!("{:.*}", precision, n),
+                    None => interpreter.stringify(value),
+                };
+                match text.strip_prefix('-') {
+                    Some(magnitude) => ("-", "", magnitude.to_owned()),
+                    None => (if self.sign { "+" } else { "" }, "", text),
+                }
+            }
+            (Kind::Display, None) => {
+                if self.sign || self.zero || self.alternate {
+                    return Err(format!("can't use '+', '#' or '0' with a {}", type_name(value)));
+                }
+                let text = interpreter.stringify(value);
+                match self.precision {
+                    Some(precision) => ("", "", text.chars().take(precision).collect()),
+                    None => ("", "", text),
+                }
+            }
+            (kind, Some(n)) if n.fract() == 0.0 && n.abs() < u64::MAX as f64 => {
+                if self.precision.is_some() {
+                    return Err("can't have a precision with x, X, b or o".to_owned());
+                }
+                let magnitude = n.abs() as u64;
+                let (prefix, body) = match kind {
+                    Kind::Hex => ("0x", format!("{:x}", magnitude)),
+                    Kind::UpperHex => ("0X", format!("{:X}", magnitude)),
+                    Kind::Binary => ("0b", format!("{:b}", magnitude)),
+                    _ => ("0o", format!("{:o}", magnitude)),
+                };
+                let sign = if n < 0.0 { "-" } else if self.sign { "+" } else { "" };
+                (sign, if self.alternate { prefix } else { "" }, body)
+            }
+            (_, Some(n)) => return Err(format!("needs an integer but got {}", interpreter.stringify(&Some(LiteralType::Number(n))))),
+            (_, None) if value.is_none() => return Err("needs an integer but got nil".to_owned()),
+            (_, None) => return Err(format!("needs an integer but got a {}", type_name(value))),
+        };
+
+        let length = sign.chars().count() + prefix.chars().count() + body.chars().count();
+        let padding = self.width.saturating_sub(length);
+
+        // Zeros go between the sign and the digits, whatever the alignment.
+        if self.zero {
+            return Ok(format!("{}{}{}{}", sign, prefix, "0".repeat(padding), body));
+        }
+
+        let text = format!("{}{}{}", sign, prefix, body);
+        let align = self.align.unwrap_or(if number.is_some() { Align::Right } else { Align::Left });
+        let (before, after) = match align {
+            Align::Left => (0, padding),
+            Align::Right => (padding, 0),
+            Align::Center => (padding / 2, padding - padding / 2),
+        };
+        let fill = |count: usize| self.fill.to_string().repeat(count);
+        return Ok(format!("{}{}{}", fill(before), text, fill(after)));
+    }
+}
+
+// The width or precision starting at `start`, if there is one, and where it
+// ends; None if it is more than MAX_WIDTH.
+fn digits(chars: &[char], start: usize) -> Option<(Option<usize>, usize)> {
+    let mut end = start;
+    while chars.get(end).is_some_and(|c| c.is_ascii_digit()) {
+        end += 1;
+    }
+    if end == start {
+        return Some((None, end));
+    }
+    let text: String = chars[start..end].iter().collect();
+    let number = text.parse().ok().filter(|number| *number <= MAX_WIDTH)?;
+    return Some((Some(number), end));
+}
+
+fn type_name(value: &Option<LiteralType>) -> &'static str {
+    match value {
+        Some(value) => value.type_name(),
+        None => "nil",
+    }
+}
@@ -0,0 +1,59 @@
+// Natives reading standard input, so scripts can be used as filters.
+use std::io::{self, BufRead, Read, Write};
+use crate::interpreter::Interpreter;
+use crate::lox_iterator::LoxIterator;
+use crate::native::{error, NativeResult};
+use crate::token::{LiteralType, Token};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("readLine", 0, read_line);
+    interpreter.register_native("readAll", 0, read_all);
+    interpreter.register_native("lines", 0, lines);
+}
+
+fn trim_newline(mut line: String) -> String {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    return line;
+}
+
+// The next line without its line ending, or nil at the end of input.
+pub fn read_line(_interpreter: &mut Interpreter, paren: &Token, _arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    // Whatever was printed as a prompt has to be visible before we block.
+    let _ = io::stdout().flush();
+
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) => return Ok(None),
+        Ok(_) => return Ok(Some(LiteralType::String(trim_newline(line)))),
+        Err(e) => return Err(error(paren, &format!("readLine: {}.", e))),
+    }
+}
+
+pub fn read_all(_interpreter: &mut Interpreter, paren: &Token, _arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let _ = io::stdout().flush();
+
+    let mut contents = String::new();
+    match io::stdin().lock().read_to_string(&mut contents) {
+        Ok(_) => return Ok(Some(LiteralType::String(contents))),
+        Err(e) => return Err(error(paren, &format!("readAll: {}.", e))),
+    }
+}
+
+// Lines of standard input for a for-in loop, read one at a time as the loop
+// asks for them. Iteration stops at the end of input or at a read error.
+pub fn lines(_interpreter: &mut Interpreter, _paren: &Token, _arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let lines = std::iter::from_fn(|| {
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => return Some(Some(LiteralType::String(trim_newline(line)))),
+        }
+    });
+    return Ok(Some(LiteralType::Iterator(LoxIterator::new(lines))));
+}
@@ -1,6 +1,22 @@
-use std::{rc::Rc, time::{SystemTime, UNIX_EPOCH}};
-use crate::{environment::Environment, expression::{self, Variable}, runtime_error, statement::{self}, token::{LiteralType, Token, TokenType}};
-use crate::lox_callable::{LoxFunction, LoxAnonymous, LoxCallables, LoxCallable};
+use std::{collections::HashMap, ptr, rc::Rc};
+use crate::fiber::{self, Scheduler};
+use crate::byte_string;
+use crate::{environment::Environment, expression::{self, Variable}, runtime_error, statement::{self}, token::{self, LiteralType, Token, TokenType}};
+use crate::lox_callable::{LoxFunction, LoxCallables, LoxCallable};
+use crate::native::{LoxNative, NativeResult};
+use crate::{file_system, format, input, json, math, process, random, regex_natives, string, time, types};
+use crate::random::Random;
+use crate::time::{Clock, SystemClock};
+use crate::lox_enum::LoxEnum;
+use crate::lox_range::LoxRange;
+use crate::lox_module::LoxModule;
+
+// Roughly where the stack currently ends; stacks grow down.
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0u8;
+    return ptr::addr_of!(marker) as usize;
+}
 
 pub struct RuntimeError {
     pub token: Token,
@@ -26,68 +42,278 @@ impl Return {
     }
 }
 
+// A call in tail position (`return f(x);`). The callee and its arguments are
+// evaluated in the returning frame, which is then unwound before the call is
+// made, so tail recursion runs in constant Rust stack space.
+pub struct TailCall {
+    pub callee: LoxCallables,
+    pub paren: Token,
+    pub arguments: Vec<Option<LiteralType>>,
+}
+
+impl TailCall {
+    pub fn new(callee: LoxCallables, paren: Token, arguments: Vec<Option<LiteralType>>) -> Self {
+        Self { callee, paren, arguments }
+    }
+}
+
 pub enum RuntimeException {
     RuntimeError(RuntimeError),
     Return(Return),
+    TailCall(TailCall),
+    // `break` and `continue`, with the label of the loop they target, if any.
+    Break(Option<String>),
+    Continue(Option<String>),
+    // `exit(code)`, unwinding the whole script.
+    Exit(i32),
 }
 
+// How much stack is kept free below where the deepest Lox call starts, for
+// the frames it goes on to need before the next check.
+const STACK_MARGIN: usize = 256 * 1024;
+
 pub struct Interpreter {
     globals: Rc<Environment>,
     environment: Rc<Environment>,
+    scheduler: Scheduler,
+    clock: Box<dyn Clock>,
+    // The command-line arguments after the script's path.
+    args: Vec<String>,
+    // Keeps untrusted scripts from running other programs.
+    sandboxed: bool,
+    random: Random,
+    // What `register_module` has gathered so far for the module it is filling.
+    module_members: Option<HashMap<String, Option<LiteralType>>>,
+    // Set by `module_only` while defining members that aren't also globals.
+    module_only: bool,
+    // The lowest stack address a Lox call may start at, so runaway recursion
+    // is a runtime error instead of overflowing the stack. Zero if unknown.
+    stack_limit: usize,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
         let globals = Rc::new(Environment::new(None));
-
-        globals.define("clock".to_owned(),
-            Some(LiteralType::LoxCallable(LoxCallables::LoxAnonymous(
-                Box::new(LoxAnonymous::new(|_interpreter, _arguments| {
-                    Ok(Some(LiteralType::Number(
-                        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64(),
-                    )))
-                }, || 0,
-                )),
-            ))),
-        );
-
         let environment = Rc::clone(&globals);
 
-        Self {
+        let mut interpreter = Self {
             globals,
             environment,
+            scheduler: Scheduler::new(),
+            clock: Box::new(SystemClock::new()),
+            args: Vec::new(),
+            sandboxed: false,
+            random: Random::new(),
+            module_members: None,
+            module_only: false,
+            stack_limit: 0,
+        };
+
+        interpreter.register_module("time", time::register);
+        interpreter.register_module("fiber", fiber::register);
+        interpreter.register_module("bytes", byte_string::register);
+        interpreter.register_module("string", |interpreter| {
+            string::register(interpreter);
+            format::register(interpreter);
+        });
+        interpreter.register_module("math", math::register);
+        interpreter.register_module("fs", file_system::register);
+        interpreter.register_module("io", input::register);
+        interpreter.register_module("types", types::register);
+        interpreter.register_module("json", json::register);
+        interpreter.register_module("regex", regex_natives::register);
+        interpreter.register_module("process", process::register);
+        interpreter.register_module("rand", random::register);
+        // Older than the modules, so only a global: `time.now` is the same.
+        interpreter.register_native("clock", 0, time::now);
+        interpreter.register_native("jsonParse", 1, json::json_parse);
+        interpreter.register_native("jsonStringify", 2, json::json_stringify);
+
+        return interpreter;
+    }
+
+    // Defines a module holding every global `register` defines, e.g.
+    // `math.sqrt` for the global `sqrt`.
+    pub fn register_module<F>(&mut self, name: &str, register: F)
+    where
+        F: FnOnce(&mut Interpreter),
+    {
+        let outer = self.module_members.replace(HashMap::new());
+        register(self);
+        let members = std::mem::replace(&mut self.module_members, outer).unwrap();
+        self.define_global(name, Some(LiteralType::Module(LoxModule::new(name, members))));
+    }
+
+    // Makes a Rust closure callable from Lox as a global function.
+    pub fn register_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: FnMut(&mut Interpreter, &Token, Vec<Option<LiteralType>>) -> NativeResult + 'static,
+    {
+        self.define_native(LoxNative::new_mut(name, arity, function));
+    }
+
+    // For natives that may be running more than once at a time, like ones
+    // that suspend the calling fiber.
+    pub fn register_reentrant_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&mut Interpreter, &Token, Vec<Option<LiteralType>>) -> NativeResult + 'static,
+    {
+        self.define_native(LoxNative::new(name, arity, Rc::new(function)));
+    }
+
+    // For natives taking at least `arity` arguments and any number more.
+    pub fn register_variadic_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: FnMut(&mut Interpreter, &Token, Vec<Option<LiteralType>>) -> NativeResult + 'static,
+    {
+        self.define_native(LoxNative::new_mut(name, arity, function).variadic());
+    }
+
+    // Inside `register_module` this also defines a member of the module.
+    pub fn define_global(&mut self, name: &str, value: Option<LiteralType>) {
+        if let Some(members) = &mut self.module_members {
+            members.insert(name.to_owned(), value.clone());
+            if self.module_only {
+                return;
+            }
         }
+        self.globals.define(name.to_owned(), value);
+    }
+
+    // Whatever `define` defines only becomes a member of the module being
+    // registered, for names that are taken or read badly as globals, like
+    // `regex.split` next to the string `split`.
+    pub fn module_only<F>(&mut self, define: F)
+    where
+        F: FnOnce(&mut Interpreter),
+    {
+        let outer = std::mem::replace(&mut self.module_only, true);
+        define(self);
+        self.module_only = outer;
     }
 
-    pub fn interpret_expression(&mut self, expression: &expression::Expr) {
+    fn define_native(&mut self, native: LoxNative) {
+        let name = native.name().to_owned();
+        self.define_global(&name, Some(LiteralType::LoxCallable(LoxCallables::LoxNative(Box::new(native)))));
+    }
+
+    // Both return the exit code the script asked for with `exit`, if any.
+    pub fn interpret_expression(&mut self, expression: &expression::Expr) -> Option<i32> {
         let result = self.evaluate(&Box::new(expression));
         if result.is_ok() {
             println!("{}", self.stringify(&result.ok().unwrap()));
-            return;
+            return None;
         }
 
         match result.unwrap_err() {
             RuntimeException::RuntimeError(run_error) => runtime_error(run_error),
+            RuntimeException::Exit(code) => return Some(code),
             _ => {}
         }
+        return None;
     }
 
-    pub fn interpret(&mut self, statements: Vec<statement::Statement>) {
+    pub fn interpret(&mut self, statements: Vec<statement::Statement>) -> Option<i32> {
         let mut error: Option<RuntimeException> = None;
         for statement in statements {
-            let result = self.execute(&statement);
-            if result.is_err() {
-                error = result.err();
-                break;
+            match self.execute(&statement) {
+                Ok(()) => {}
+                // `return` outside of any function ends the script, but
+                // `return f(x);` still makes its call first, before any
+                // fibers or deferred statements run.
+                Err(RuntimeException::TailCall(mut tail_call)) => {
+                    error = tail_call.callee.call(self, &tail_call.paren, tail_call.arguments).err();
+                    break;
+                }
+                Err(RuntimeException::Return(_)) => break,
+                Err(exception) => {
+                    error = Some(exception);
+                    break;
+                }
             }
         }
 
+        // Fibers that are still ready to run get to finish after the script.
+        if error.is_none() {
+            error = fiber::run_fibers(self).err();
+        }
+        if error.is_none() {
+            error = fiber::check_blocked(self).err();
+        }
+        fiber::cancel_fibers(self);
+
+        // The script's own scope exits only once those fibers are done.
+        let deferred = self.run_deferred(&self.environment(), Ok(()));
+        if error.is_none() {
+            error = deferred.err();
+        }
+
         if error.is_some() {
             match error.unwrap() {
                 RuntimeException::RuntimeError(run_error) => runtime_error(run_error),
+                RuntimeException::Exit(code) => return Some(code),
                 _ => {}
             }
         }
+        return None;
+    }
+
+    pub fn scheduler(&mut self) -> &mut Scheduler {
+        &mut self.scheduler
+    }
+
+    pub fn random(&mut self) -> &mut Random {
+        &mut self.random
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    // For a stack of `size` bytes whose top the caller is running near.
+    pub fn set_stack_size(&mut self, size: usize) {
+        self.stack_limit = stack_position().saturating_sub(size.saturating_sub(STACK_MARGIN));
+    }
+
+    pub fn stack_limit(&self) -> usize {
+        self.stack_limit
+    }
+
+    pub fn set_stack_limit(&mut self, stack_limit: usize) {
+        self.stack_limit = stack_limit;
+    }
+
+    // Checked before every call of a Lox function.
+    pub fn stack_exhausted(&self) -> bool {
+        stack_position() < self.stack_limit
+    }
+
+    pub fn sandboxed(&self) -> bool {
+        self.sandboxed
+    }
+
+    pub fn set_sandboxed(&mut self, sandboxed: bool) {
+        self.sandboxed = sandboxed;
+    }
+
+    pub fn environment(&self) -> Rc<Environment> {
+        Rc::clone(&self.environment)
+    }
+
+    pub fn set_environment(&mut self, environment: Rc<Environment>) {
+        self.environment = environment;
     }
 
     fn execute(&mut self, statement: &statement::Statement) -> Result<(), RuntimeException> {
@@ -95,8 +321,9 @@ impl Interpreter {
         return Ok(());
     }
 
-    pub fn execute_block(&mut self, statements: &Vec<statement::Statement>, environment: Rc<Environment>) -> Result<(), RuntimeException> {
+    pub fn execute_block(&mut self, statements: &[statement::Statement], environment: Rc<Environment>) -> Result<(), RuntimeException> {
         let previous = Rc::clone(&self.environment);
+        let environment_of_block = Rc::clone(&environment);
         self.environment = environment;
 
         let mut error: Result<(), RuntimeException> = Ok(());
@@ -109,16 +336,34 @@ impl Interpreter {
             }
         }
 
+        error = self.run_deferred(&environment_of_block, error);
         self.environment = previous;
 
         return error;
     }
 
+    // Runs the statements deferred in a scope that is being exited, last
+    // registered first. All of them run even if one fails. A runtime error or
+    // `exit` already unwinding the scope wins; otherwise the first error from
+    // a deferred statement replaces a normal exit or a `return`.
+    fn run_deferred(&mut self, environment: &Rc<Environment>, result: Result<(), RuntimeException>) -> Result<(), RuntimeException> {
+        let mut result = result;
+        for statement in environment.take_deferred().iter().rev() {
+            let deferred_result = self.execute(statement);
+            match (&result, deferred_result) {
+                (Err(RuntimeException::RuntimeError(_) | RuntimeException::Exit(_)), _) => {}
+                (_, Err(error)) => result = Err(error),
+                (_, Ok(())) => {}
+            }
+        }
+        return result;
+    }
+
     fn evaluate(&mut self, expression: &expression::Expr) -> Result<Option<LiteralType>, RuntimeException> {
         expression.accept(self)
     }
 
-    fn stringify(&self, value: &Option<LiteralType>) -> String {
+    pub fn stringify(&self, value: &Option<LiteralType>) -> String {
         match value {
             Some(result) => {
                 match result {
@@ -130,7 +375,7 @@ impl Interpreter {
         }
     }
 
-    fn is_truthy(&self, value: &Option<LiteralType>) -> bool {
+    pub fn is_truthy(&self, value: &Option<LiteralType>) -> bool {
         if value.is_none() {
             return false;
         }
@@ -141,62 +386,40 @@ impl Interpreter {
         }
     }
 
-    fn check_number_operand(&self, operator: &Token, operand: &Option<LiteralType>) -> Result<f64, RuntimeException> {
-        match operand {
-            Some(LiteralType::Number(value)) => return Ok(*value),
-            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(operator, "Operand must be a number."))),
-        }
-    }
-
-    fn check_number_operands(&self, operator: &Token, left: &Option<LiteralType>, right: &Option<LiteralType>) -> Result<(f64, f64), RuntimeException> {
-        match (left, right) {
-            (Some(LiteralType::Number(x)), Some(LiteralType::Number(y))) => {
-                return Ok((*x, *y));
-            }
-            _ => { return Err(RuntimeException::RuntimeError(RuntimeError::new(operator, "Operand must be a number."))); }
+    // Evaluates the arguments of a call and checks the callee and arity,
+    // leaving only the call itself to be made.
+    fn prepare_call(&mut self, call: &expression::Call, callee: Option<LiteralType>) -> Result<(LoxCallables, Vec<Option<LiteralType>>), RuntimeException> {
+        let mut arguments = Vec::new();
+        for argument in &call.arguments {
+            arguments.push(self.evaluate(&Box::new(argument))?);
         }
-    }
-}
 
-impl expression::Visitor for Interpreter {
-    type Output = Result<Option<LiteralType>, RuntimeException>;
-
-    fn visit_literal(&mut self, literal: &expression::Literal) -> Self::Output {
-        return Ok(literal.value.to_owned());
-    }
+        let function = match callee {
+            Some(LiteralType::LoxCallable(callable)) => Ok(callable),
+            _ => Err(RuntimeException::RuntimeError(RuntimeError::new(&call.paren, "Can only call functions and classes.")))
+        }?;
 
-    fn visit_grouping(&mut self, grouping: &expression::Grouping) -> Self::Output {
-        return self.evaluate(&grouping.expression);
-    }
-    
-    fn visit_unary(&mut self, unary: &expression::Unary) -> Self::Output {
-        let right = self.evaluate(&unary.right)?;
-        
-        match unary.operator.token_type {
-            TokenType::Minus => {
-                let number = self.check_number_operand(&unary.operator, &right)?;
-                return Ok(Some(LiteralType::Number(-number)));
+        if function.is_variadic() {
+            if arguments.len() < function.arity() {
+                return Err(RuntimeException::RuntimeError(RuntimeError::new(&call.paren, &format!("Expected at least {} arguments but got {}.", function.arity(), arguments.len()))));
             }
-            TokenType::Bang => {
-                return Ok(Some(LiteralType::Boolean(!self.is_truthy(&right))));
-            }
-            _ => return Ok(None),
+        } else if arguments.len() != function.arity() {
+            return Err(RuntimeException::RuntimeError(RuntimeError::new(&call.paren, &format!("Expected {} arguments but got {}.", function.arity(), arguments.len()))));
         }
-    }
 
-    fn visit_binary(&mut self, binary: &expression::Binary) -> Self::Output {
-        let left = self.evaluate(&binary.left)?;
-        let right = self.evaluate(&binary.right)?;
+        return Ok((function, arguments));
+    }
 
-        match binary.operator.token_type {
+    fn binary_operation(&self, operator: &Token, left: Option<LiteralType>, right: Option<LiteralType>) -> Result<Option<LiteralType>, RuntimeException> {
+        match operator.token_type {
             TokenType::Star => {
                 let (left_number, right_number) = 
-                    self.check_number_operands(&binary.operator, &left, &right)?;
+                    self.check_number_operands(operator, &left, &right)?;
                 return Ok(Some(LiteralType::Number(left_number * right_number)));
             }
             TokenType::Slash => {
                 let (left_number, right_number) = 
-                    self.check_number_operands(&binary.operator, &left, &right)?;
+                    self.check_number_operands(operator, &left, &right)?;
                 return Ok(Some(LiteralType::Number(left_number / right_number)));
             }
             TokenType::Plus => {
@@ -207,20 +430,23 @@ impl expression::Visitor for Interpreter {
                     (Some(LiteralType::String(x)), Some(LiteralType::String(y))) => {
                         return Ok(Some(LiteralType::String(format!("{}{}", x, y))));
                     },
+                    (Some(LiteralType::Bytes(x)), Some(LiteralType::Bytes(y))) => {
+                        return Ok(Some(LiteralType::Bytes(byte_string::concat(&x, &y))));
+                    },
                     _ => {},
                 }
 
-                return Err(RuntimeException::RuntimeError(RuntimeError::new(&binary.operator, "Operands must be two numbers or two strings.")));
+                return Err(RuntimeException::RuntimeError(RuntimeError::new(operator, "Operands must be two numbers or two strings.")));
             }
             TokenType::Minus => {
                 let (left_number, right_number) = 
-                    self.check_number_operands(&binary.operator, &left, &right)?;
+                    self.check_number_operands(operator, &left, &right)?;
                 return Ok(Some(LiteralType::Number(left_number - right_number)));
             }
             TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
                 let (left_number, right_number) = 
-                    self.check_number_operands(&binary.operator, &left, &right)?;
-                match binary.operator.token_type {
+                    self.check_number_operands(operator, &left, &right)?;
+                match operator.token_type {
                     TokenType::Greater => return Ok(Some(LiteralType::Boolean(left_number > right_number))),
                     TokenType::GreaterEqual => return Ok(Some(LiteralType::Boolean(left_number >= right_number))),
                     TokenType::Less => return Ok(Some(LiteralType::Boolean(left_number < right_number))),
@@ -234,10 +460,246 @@ impl expression::Visitor for Interpreter {
             TokenType::BangEqual => {
                 return Ok(Some(LiteralType::Boolean(left != right)));
             }
-            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(&binary.operator, "Invalid operator when evaluating binary."))),
+            TokenType::In => {
+                return Ok(Some(LiteralType::Boolean(self.contains(operator, &right, &left)?)));
+            }
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(operator, "Invalid operator when evaluating binary."))),
+        }
+    }
+
+    // The values a for-in loop steps through, produced as the loop asks for them.
+    fn iterate(&self, token: &Token, iterable: Option<LiteralType>) -> Result<Box<dyn Iterator<Item = Option<LiteralType>>>, RuntimeException> {
+        match iterable {
+            Some(LiteralType::Enum(enumeration)) => {
+                return Ok(Box::new(LoxEnum::all_variants(&enumeration).into_iter()
+                    .map(|variant| Some(LiteralType::EnumVariant(variant)))));
+            }
+            Some(LiteralType::Tuple(elements)) => {
+                return Ok(Box::new((0..elements.len()).map(move |i| elements[i].clone())));
+            }
+            Some(LiteralType::Range(range)) => {
+                return Ok(Box::new(range.iter().map(|n| Some(LiteralType::Number(n as f64)))));
+            }
+            Some(LiteralType::Iterator(iterator)) => return Ok(Box::new(iterator)),
+            Some(LiteralType::Map(map)) => {
+                return Ok(Box::new((0..map.len()).map(move |i| Some(LiteralType::String(map.entries()[i].0.clone())))));
+            }
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(token, "Can only iterate over enums, tuples, ranges, maps and iterators."))),
+        }
+    }
+
+    // `value in collection`.
+    fn contains(&self, operator: &Token, collection: &Option<LiteralType>, value: &Option<LiteralType>) -> Result<bool, RuntimeException> {
+        match (collection, value) {
+            (Some(LiteralType::Range(range)), Some(LiteralType::Number(n))) => return Ok(range.contains(*n)),
+            (Some(LiteralType::Range(_)), _) => return Ok(false),
+            (Some(LiteralType::Tuple(elements)), _) => return Ok(elements.contains(value)),
+            (Some(LiteralType::String(string)), Some(LiteralType::String(substring))) => return Ok(string.contains(substring.as_str())),
+            (Some(LiteralType::String(_)), _) => {
+                return Err(RuntimeException::RuntimeError(RuntimeError::new(operator, "Left operand of 'in' must be a string when testing a string.")));
+            }
+            (Some(LiteralType::Bytes(bytes)), Some(LiteralType::Number(n))) => return Ok(bytes.iter().any(|byte| *byte as f64 == *n)),
+            (Some(LiteralType::Enum(enumeration)), Some(LiteralType::EnumVariant(variant))) => return Ok(variant.enumeration == *enumeration),
+            (Some(LiteralType::Enum(_)), _) => return Ok(false),
+            (Some(LiteralType::Map(map)), Some(LiteralType::String(key))) => return Ok(map.contains_key(key)),
+            (Some(LiteralType::Map(_)), _) => return Ok(false),
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(operator, "Right operand of 'in' must be a range, tuple, string, byte string, enum or map."))),
         }
     }
 
+    fn check_slice(&self, bracket: &Token, range: &LoxRange, length: usize) -> Result<(usize, usize), RuntimeException> {
+        match range.slice_bounds(length) {
+            Some(bounds) => return Ok(bounds),
+            None => return Err(RuntimeException::RuntimeError(RuntimeError::new(bracket, &format!("Slice {} out of range for length {}.", range, length)))),
+        }
+    }
+
+    // Whether a loop labeled `label` runs another iteration after its body
+    // finished with `result`. Breaks and continues aimed at an outer loop
+    // keep unwinding.
+    fn loop_continues(&self, label: &Option<Token>, result: Result<(), RuntimeException>) -> Result<bool, RuntimeException> {
+        let targets_this_loop = |target: &Option<String>| match target {
+            Some(target) => label.as_ref().is_some_and(|label| label.lexeme == *target),
+            None => true,
+        };
+
+        match result {
+            Err(RuntimeException::Break(target)) if targets_this_loop(&target) => return Ok(false),
+            Err(RuntimeException::Continue(target)) if targets_this_loop(&target) => return Ok(true),
+            Err(error) => return Err(error),
+            Ok(()) => return Ok(true),
+        }
+    }
+
+    fn check_index(&self, bracket: &Token, index: &Option<LiteralType>, length: usize) -> Result<usize, RuntimeException> {
+        match index {
+            Some(LiteralType::Number(n)) if n.fract() == 0.0 => {
+                if *n < 0.0 || *n >= length as f64 {
+                    return Err(RuntimeException::RuntimeError(RuntimeError::new(bracket, &format!("Index {} out of range for length {}.", n, length))));
+                }
+                return Ok(*n as usize);
+            }
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(bracket, "Index must be an integer."))),
+        }
+    }
+
+    fn is_comparison(&self, operator: &Token) -> bool {
+        match operator.token_type {
+            TokenType::EqualEqual | TokenType::BangEqual
+            | TokenType::Greater | TokenType::GreaterEqual
+            | TokenType::Less | TokenType::LessEqual | TokenType::In => return true,
+            _ => return false,
+        }
+    }
+
+    fn check_number_operand(&self, operator: &Token, operand: &Option<LiteralType>) -> Result<f64, RuntimeException> {
+        match operand {
+            Some(LiteralType::Number(value)) => return Ok(*value),
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(operator, "Operand must be a number."))),
+        }
+    }
+
+    fn check_number_operands(&self, operator: &Token, left: &Option<LiteralType>, right: &Option<LiteralType>) -> Result<(f64, f64), RuntimeException> {
+        match (left, right) {
+            (Some(LiteralType::Number(x)), Some(LiteralType::Number(y))) => {
+                return Ok((*x, *y));
+            }
+            _ => { return Err(RuntimeException::RuntimeError(RuntimeError::new(operator, "Operand must be a number."))); }
+        }
+    }
+
+    // A nil receiver of `?.` skips the rest of the chain of calls, property
+    // accesses and indexing it is in, so `a?.b.c` is nil when `a` is. These
+    // return None when the chain was skipped.
+    fn evaluate_chain(&mut self, expression: &expression::Expr) -> Result<Option<Option<LiteralType>>, RuntimeException> {
+        match expression {
+            expression::Expr::Call(call) => return self.chain_call(call),
+            expression::Expr::Get(get) => return self.chain_get(get),
+            expression::Expr::Index(index) => return self.chain_index(index),
+            _ => return Ok(Some(self.evaluate(expression)?)),
+        }
+    }
+
+    fn chain_receiver(&mut self, expression: &expression::Expr, optional: bool) -> Result<Option<Option<LiteralType>>, RuntimeException> {
+        match self.evaluate_chain(expression)? {
+            Some(None) if optional => return Ok(None),
+            receiver => return Ok(receiver),
+        }
+    }
+
+    fn chain_call(&mut self, call: &expression::Call) -> Result<Option<Option<LiteralType>>, RuntimeException> {
+        let callee = match self.chain_receiver(&call.callee, call.optional)? {
+            Some(callee) => callee,
+            None => return Ok(None),
+        };
+
+        let (mut function, arguments) = self.prepare_call(call, callee)?;
+        return Ok(Some(function.call(self, &call.paren, arguments)?));
+    }
+
+    fn chain_index(&mut self, index: &expression::Index) -> Result<Option<Option<LiteralType>>, RuntimeException> {
+        let object = match self.chain_receiver(&index.object, false)? {
+            Some(object) => object,
+            None => return Ok(None),
+        };
+        let position = self.evaluate(&index.index)?;
+        return Ok(Some(self.index(index, object, position)?));
+    }
+
+    fn chain_get(&mut self, get: &expression::Get) -> Result<Option<Option<LiteralType>>, RuntimeException> {
+        let object = match self.chain_receiver(&get.object, get.optional)? {
+            Some(object) => object,
+            None => return Ok(None),
+        };
+        return Ok(Some(self.property(get, object)?));
+    }
+
+    fn index(&mut self, index: &expression::Index, object: Option<LiteralType>, position: Option<LiteralType>) -> Result<Option<LiteralType>, RuntimeException> {
+        // Indexing with a range takes a slice. Strings are indexed by code point.
+        match (object, position) {
+            (Some(LiteralType::Bytes(bytes)), Some(LiteralType::Range(range))) => {
+                let (start, end) = self.check_slice(&index.bracket, &range, bytes.len())?;
+                return Ok(Some(LiteralType::Bytes(bytes.slice(start..end))));
+            }
+            (Some(LiteralType::Bytes(bytes)), position) => {
+                let i = self.check_index(&index.bracket, &position, bytes.len())?;
+                return Ok(Some(LiteralType::Number(bytes[i] as f64)));
+            }
+            (Some(LiteralType::Tuple(elements)), Some(LiteralType::Range(range))) => {
+                let (start, end) = self.check_slice(&index.bracket, &range, elements.len())?;
+                return Ok(Some(LiteralType::Tuple(Rc::new(elements[start..end].to_vec()))));
+            }
+            (Some(LiteralType::Tuple(elements)), position) => {
+                let i = self.check_index(&index.bracket, &position, elements.len())?;
+                return Ok(elements[i].clone());
+            }
+            (Some(LiteralType::String(string)), Some(LiteralType::Range(range))) => {
+                let (start, end) = self.check_slice(&index.bracket, &range, string.chars().count())?;
+                return Ok(Some(LiteralType::String(string.chars().skip(start).take(end - start).collect())));
+            }
+            (Some(LiteralType::String(string)), position) => {
+                let i = self.check_index(&index.bracket, &position, string.chars().count())?;
+                return Ok(Some(LiteralType::String(string.chars().nth(i).unwrap().to_string())));
+            }
+            // A missing key gives nil, so `map["key"] ?? default` works.
+            (Some(LiteralType::Map(map)), Some(LiteralType::String(key))) => return Ok(map.get(&key).cloned().flatten()),
+            (Some(LiteralType::Map(_)), _) => return Err(RuntimeException::RuntimeError(RuntimeError::new(&index.bracket, "Map keys must be strings."))),
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(&index.bracket, "Only strings, byte strings, tuples and maps can be indexed."))),
+        }
+    }
+
+    fn property(&mut self, get: &expression::Get, object: Option<LiteralType>) -> Result<Option<LiteralType>, RuntimeException> {
+        match object {
+            Some(LiteralType::Enum(enumeration)) => {
+                match LoxEnum::variant(&enumeration, &get.name.lexeme) {
+                    Some(variant) => return Ok(Some(LiteralType::EnumVariant(variant))),
+                    None => return Err(RuntimeException::RuntimeError(RuntimeError::new(&get.name, &format!("Undefined variant '{}' on enum {}.", get.name.lexeme, enumeration.name)))),
+                }
+            }
+            Some(LiteralType::Module(module)) => {
+                match module.get(&get.name.lexeme) {
+                    Some(member) => return Ok(member.clone()),
+                    None => return Err(RuntimeException::RuntimeError(RuntimeError::new(&get.name, &format!("Undefined member '{}' in module {}.", get.name.lexeme, module.name)))),
+                }
+            }
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(&get.name, "Only enums and modules have properties."))),
+        }
+    }
+}
+
+impl expression::Visitor for Interpreter {
+    type Output = Result<Option<LiteralType>, RuntimeException>;
+
+    fn visit_literal(&mut self, literal: &expression::Literal) -> Self::Output {
+        return Ok(literal.value.to_owned());
+    }
+
+    fn visit_grouping(&mut self, grouping: &expression::Grouping) -> Self::Output {
+        return self.evaluate(&grouping.expression);
+    }
+    
+    fn visit_unary(&mut self, unary: &expression::Unary) -> Self::Output {
+        let right = self.evaluate(&unary.right)?;
+        
+        match unary.operator.token_type {
+            TokenType::Minus => {
+                let number = self.check_number_operand(&unary.operator, &right)?;
+                return Ok(Some(LiteralType::Number(-number)));
+            }
+            TokenType::Bang => {
+                return Ok(Some(LiteralType::Boolean(!self.is_truthy(&right))));
+            }
+            _ => return Ok(None),
+        }
+    }
+
+    fn visit_binary(&mut self, binary: &expression::Binary) -> Self::Output {
+        let left = self.evaluate(&binary.left)?;
+        let right = self.evaluate(&binary.right)?;
+
+        return self.binary_operation(&binary.operator, left, right);
+    }
+
     fn visit_variable(&mut self, variable: &Variable) -> Self::Output {
         return Ok(self.environment.get(&variable.name)?);
     }
@@ -263,6 +725,11 @@ impl expression::Visitor for Interpreter {
                     return Ok(left);
                 }
             }
+            TokenType::QuestionQuestion => {
+                if left.is_some() {
+                    return Ok(left);
+                }
+            }
             _ => {
                 return Err(RuntimeException::RuntimeError(RuntimeError::new(&logical.operator, "Invalid operator when evaluating logical.")))
             }
@@ -271,28 +738,51 @@ impl expression::Visitor for Interpreter {
         return self.evaluate(&logical.right);
     }
 
+    // `chain_call` without going through it, so deep recursion doesn't pay
+    // for an extra frame per call.
     fn visit_call(&mut self, call: &expression::Call) -> Self::Output {
-        let callee = self.evaluate(&call.callee)?;
+        let callee = match self.chain_receiver(&call.callee, call.optional)? {
+            Some(callee) => callee,
+            None => return Ok(None),
+        };
 
-        let mut arguments = Vec::new();
-        for argument in &call.arguments {
-            arguments.push(self.evaluate(&Box::new(argument))?);
-        }
+        let (mut function, arguments) = self.prepare_call(call, callee)?;
+        return function.call(self, &call.paren, arguments);
+    }
 
-        let mut function = match callee {
-            Some(LiteralType::LoxCallable(callable)) => Ok(callable),
-            _ => Err(RuntimeException::RuntimeError(RuntimeError::new(&call.paren, "Can only call functions and classes.")))
-        }?;
+    fn visit_tuple(&mut self, tuple: &expression::Tuple) -> Self::Output {
+        let mut elements = Vec::new();
+        for element in &tuple.elements {
+            elements.push(self.evaluate(element)?);
+        }
+        return Ok(Some(LiteralType::Tuple(Rc::new(elements))));
+    }
 
-        if arguments.len() != function.arity() {
-            return Err(RuntimeException::RuntimeError(RuntimeError::new(&call.paren, &format!("Expected {} arguments but got {}.", function.arity(), arguments.len()))));
+    fn visit_range(&mut self, range: &expression::Range) -> Self::Output {
+        let start = self.evaluate(&range.start)?;
+        let end = self.evaluate(&range.end)?;
+
+        // Past 2^53 not every integer is a number, so such ranges couldn't be
+        // counted or iterated exactly.
+        let limit = (1u64 << 53) as f64;
+        match (start, end) {
+            (Some(LiteralType::Number(start)), Some(LiteralType::Number(end))) if start.fract() == 0.0 && end.fract() == 0.0 => {
+                if start.abs() > limit || end.abs() > limit {
+                    return Err(RuntimeException::RuntimeError(RuntimeError::new(&range.operator, "Range bounds must be between -2^53 and 2^53.")));
+                }
+                let inclusive = range.operator.token_type == TokenType::DotDotEqual;
+                return Ok(Some(LiteralType::Range(LoxRange::new(start as i64, end as i64, inclusive))));
+            }
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(&range.operator, "Range bounds must be integers."))),
         }
+    }
 
-        let result = function.call(self, arguments);
-        return match result {
-            Err(RuntimeException::Return(r#return)) => Ok(r#return.value),
-            _ => result,
-        };
+    fn visit_index(&mut self, index: &expression::Index) -> Self::Output {
+        return Ok(self.chain_index(index)?.flatten());
+    }
+
+    fn visit_get(&mut self, get: &expression::Get) -> Self::Output {
+        return Ok(self.chain_get(get)?.flatten());
     }
 }
 
@@ -322,6 +812,25 @@ impl statement::Visitor for Interpreter {
         return Ok(());
     }
 
+    fn visit_destructure(&mut self, destructure: &statement::Destructure) -> Self::Output {
+        let value = self.evaluate(&destructure.initializer)?;
+
+        let elements = match value {
+            Some(LiteralType::Tuple(elements)) => elements,
+            _ => return Err(RuntimeException::RuntimeError(RuntimeError::new(&destructure.paren, "Can only destructure a tuple."))),
+        };
+
+        if elements.len() != destructure.names.len() {
+            return Err(RuntimeException::RuntimeError(RuntimeError::new(&destructure.paren, &format!("Expected {} values to unpack but got {}.", destructure.names.len(), elements.len()))));
+        }
+
+        for (name, element) in destructure.names.iter().zip(elements.iter()) {
+            self.environment.define(name.lexeme.clone(), element.clone());
+        }
+
+        return Ok(());
+    }
+
     fn visit_block(&mut self, block: &statement::Block) -> Self::Output {
         let result = self.execute_block(&block.statements, Rc::new(Environment::new(Some(&self.environment))));
         return result;
@@ -341,13 +850,48 @@ impl statement::Visitor for Interpreter {
     fn visit_while(&mut self, while_statement: &statement::While) -> Self::Output {
         let mut value = self.evaluate(&while_statement.condition)?;
         while self.is_truthy(&value) {
-            self.execute(&while_statement.body)?;
+            let result = self.execute(&while_statement.body);
+            if !self.loop_continues(&while_statement.label, result)? {
+                break;
+            }
+
+            if let Some(increment) = &while_statement.increment {
+                self.evaluate(increment)?;
+            }
             value = self.evaluate(&while_statement.condition)?;
         }
 
         return Ok(());
     }
 
+    fn visit_for_in(&mut self, for_in: &statement::ForIn) -> Self::Output {
+        let iterable = self.evaluate(&for_in.iterable)?;
+
+        for value in self.iterate(&for_in.name, iterable)? {
+            let environment = Rc::new(Environment::new(Some(&self.environment)));
+            environment.define(for_in.name.lexeme.clone(), value);
+            let result = self.execute_block(std::slice::from_ref(for_in.body.as_ref()), environment);
+            if !self.loop_continues(&for_in.label, result)? {
+                break;
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn visit_defer(&mut self, defer: &statement::Defer) -> Self::Output {
+        self.environment.defer(Rc::clone(&defer.statement));
+        return Ok(());
+    }
+
+    fn visit_break(&mut self, break_statement: &statement::Break) -> Self::Output {
+        return Err(RuntimeException::Break(break_statement.label.as_ref().map(|label| label.lexeme.clone())));
+    }
+
+    fn visit_continue(&mut self, continue_statement: &statement::Continue) -> Self::Output {
+        return Err(RuntimeException::Continue(continue_statement.label.as_ref().map(|label| label.lexeme.clone())));
+    }
+
     fn visit_function(&mut self, function: &statement::Function) -> Self::Output {
         let value = Some(LiteralType::LoxCallable(LoxCallables::LoxFunction(
             Box::new(LoxFunction::new(function.clone(), Rc::clone(&self.environment),)),
@@ -356,7 +900,61 @@ impl statement::Visitor for Interpreter {
         return Ok(());
     }
 
+    fn visit_enum(&mut self, enum_statement: &statement::Enum) -> Self::Output {
+        let variants = enum_statement.variants.iter().map(|variant| variant.lexeme.clone()).collect();
+        let value = LoxEnum::new(enum_statement.name.lexeme.clone(), variants);
+        self.environment.define(enum_statement.name.lexeme.clone(), Some(LiteralType::Enum(Rc::new(value))));
+        return Ok(());
+    }
+
+    fn visit_assert(&mut self, assert: &statement::Assert) -> Self::Output {
+        // Operands of a failed comparison are reported, so evaluate them here
+        // rather than through visit_binary.
+        let mut operands = None;
+        let value = match assert.condition.as_ref() {
+            expression::Expr::Binary(binary) if self.is_comparison(&binary.operator) => {
+                let left = self.evaluate(&binary.left)?;
+                let right = self.evaluate(&binary.right)?;
+                let value = self.binary_operation(&binary.operator, left.clone(), right.clone())?;
+                operands = Some((left, right));
+                value
+            }
+            condition => self.evaluate(condition)?,
+        };
+
+        if self.is_truthy(&value) {
+            return Ok(());
+        }
+
+        // Operands are shown as inside a tuple, so "1" and 1 differ.
+        let mut message = format!("Assertion failed: {}", assert.source);
+        if let Some((left, right)) = operands {
+            message.push_str(&format!(" (left: {}, right: {})", token::format_element(&left), token::format_element(&right)));
+        }
+        if let Some(custom_message) = &assert.message {
+            let custom_message = self.evaluate(custom_message)?;
+            message.push_str(&format!(": {}", self.stringify(&custom_message)));
+        }
+
+        return Err(RuntimeException::RuntimeError(RuntimeError::new(&assert.keyword, &message)));
+    }
+
     fn visit_return(&mut self, r#return: &statement::Return) -> Self::Output {
+        match &r#return.value {
+            // Deferred statements run when the call returns, so one with any
+            // pending has to stay on the stack until the tail call finishes.
+            Some(expression::Expr::Call(call)) if !self.environment.call_has_deferred() => {
+                let callee = match self.chain_receiver(&call.callee, call.optional)? {
+                    Some(callee) => callee,
+                    None => return Err(RuntimeException::Return(Return::new(None))),
+                };
+
+                let (callee, arguments) = self.prepare_call(call, callee)?;
+                return Err(RuntimeException::TailCall(TailCall::new(callee, call.paren.clone(), arguments)));
+            }
+            _ => {}
+        }
+
         let mut value = None;
         if r#return.value.is_some() {
             value = self.evaluate(r#return.value.as_ref().unwrap())?;
@@ -0,0 +1,398 @@
+// JSON natives. Objects become maps, arrays become tuples and null is nil.
+use std::rc::Rc;
+use crate::interpreter::Interpreter;
+use crate::lox_map::LoxMap;
+use crate::native::{check_integer, check_string, error, NativeResult};
+use crate::token::{LiteralType, Token};
+
+// Deeper documents are rejected rather than risk overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+// The most spaces `jsonStringify` indents each level by, as in JavaScript.
+const MAX_INDENT: i64 = 10;
+
+// In the module these are `json.parse` and `json.stringify`. The globals
+// `jsonParse` and `jsonStringify` are defined by `Interpreter::new`.
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.module_only(|interpreter| {
+        interpreter.register_native("parse", 1, json_parse);
+        interpreter.register_native("stringify", 2, json_stringify);
+    });
+}
+
+pub fn json_parse(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let text = check_string(paren, "jsonParse", "argument", &arguments[0])?;
+
+    let mut parser = JsonParser::new(&text);
+    match parser.parse() {
+        Ok(value) => return Ok(value),
+        Err(message) => {
+            return Err(error(paren, &format!("jsonParse: {} at line {}, column {}.", message, parser.line, parser.column)));
+        }
+    }
+}
+
+// `indent` is nil or 0 for compact output, otherwise the number of spaces,
+// up to 10, to indent each level by.
+pub fn json_stringify(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let indent = match &arguments[1] {
+        None => 0,
+        indent => check_integer(paren, "jsonStringify", "indent", indent)?,
+    };
+    if !(0..=MAX_INDENT).contains(&indent) {
+        return Err(error(paren, &format!("jsonStringify: indent must be between 0 and {}.", MAX_INDENT)));
+    }
+
+    let mut json = String::new();
+    match write_value(&mut json, &arguments[0], indent as usize, 0) {
+        Ok(()) => return Ok(Some(LiteralType::String(json))),
+        Err(message) => return Err(error(paren, &format!("jsonStringify: {}.", message))),
+    }
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    current: usize,
+    depth: usize,
+    // Where `current` is, for error messages.
+    line: usize,
+    column: usize,
+}
+
+impl JsonParser {
+    fn new(text: &str) -> Self {
+        Self {
+            chars: text.chars().collect(),
+            current: 0,
+            depth: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Option<LiteralType>, String> {
+        self.skip_whitespace();
+        let value = self.value()?;
+        self.skip_whitespace();
+
+        match self.peek() {
+            None => return Ok(value),
+            Some(c) => return Err(format!("unexpected {:?} after the JSON value", c)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Option<LiteralType>, String> {
+        match self.peek() {
+            Some('{') => return self.nested(Self::object),
+            Some('[') => return self.nested(Self::array),
+            Some('"') => return Ok(Some(LiteralType::String(self.string()?))),
+            Some('t') => return self.keyword("true", Some(LiteralType::Boolean(true))),
+            Some('f') => return self.keyword("false", Some(LiteralType::Boolean(false))),
+            Some('n') => return self.keyword("null", None),
+            Some(c) if c == '-' || c.is_ascii_digit() => return self.number(),
+            Some(c) => return Err(format!("unexpected {:?}", c)),
+            None => return Err("unexpected end of input".to_owned()),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Option<LiteralType>, String>) -> Result<Option<LiteralType>, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("nesting deeper than {} levels", MAX_DEPTH));
+        }
+        self.depth += 1;
+        let value = parse(self)?;
+        self.depth -= 1;
+        return Ok(value);
+    }
+
+    fn object(&mut self) -> Result<Option<LiteralType>, String> {
+        self.advance();
+        let mut entries = Vec::new();
+
+        self.skip_whitespace();
+        if self.match_char('}') {
+            return Ok(Some(LiteralType::Map(LoxMap::new(entries))));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.expected("a string key"));
+            }
+            let key = self.string()?;
+
+            self.skip_whitespace();
+            if !self.match_char(':') {
+                return Err(self.expected("':' after object key"));
+            }
+
+            self.skip_whitespace();
+            entries.push((key, self.value()?));
+
+            self.skip_whitespace();
+            if self.match_char('}') {
+                return Ok(Some(LiteralType::Map(LoxMap::new(entries))));
+            }
+            if !self.match_char(',') {
+                return Err(self.expected("',' or '}' in object"));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Option<LiteralType>, String> {
+        self.advance();
+        let mut elements = Vec::new();
+
+        self.skip_whitespace();
+        if self.match_char(']') {
+            return Ok(Some(LiteralType::Tuple(Rc::new(elements))));
+        }
+
+        loop {
+            self.skip_whitespace();
+            elements.push(self.value()?);
+
+            self.skip_whitespace();
+            if self.match_char(']') {
+                return Ok(Some(LiteralType::Tuple(Rc::new(elements))));
+            }
+            if !self.match_char(',') {
+                return Err(self.expected("',' or ']' in array"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.advance();
+        let mut result = String::new();
+
+        loop {
+            match self.peek() {
+                None => return Err("unterminated string".to_owned()),
+                Some('"') => {
+                    self.advance();
+                    return Ok(result);
+                }
+                Some('\\') => {
+                    self.advance();
+                    result.push(self.escape()?);
+                }
+                Some(c) if (c as u32) < 0x20 => return Err(format!("unescaped control character {:?} in string", c)),
+                Some(c) => {
+                    self.advance();
+                    result.push(c);
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        let c = match self.peek() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                self.advance();
+                return self.unicode_escape();
+            }
+            Some(c) => return Err(format!("invalid escape '\\{}'", c)),
+            None => return Err("unterminated string".to_owned()),
+        };
+        self.advance();
+        return Ok(c);
+    }
+
+    // After `\u`. Characters outside the basic plane are written as a
+    // surrogate pair: `\ud83d\ude00`.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| "invalid \\u escape".to_owned());
+        }
+
+        if !(self.match_char('\\') && self.match_char('u')) {
+            return Err("expected a low surrogate after a high surrogate".to_owned());
+        }
+        let low = self.hex4()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return Err("expected a low surrogate after a high surrogate".to_owned());
+        }
+        return char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)).ok_or_else(|| "invalid \\u escape".to_owned());
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let mut value = 0;
+        for _ in 0..4 {
+            match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => value = value * 16 + digit,
+                None => return Err(self.expected("four hex digits after \\u")),
+            }
+            self.advance();
+        }
+        return Ok(value);
+    }
+
+    fn number(&mut self) -> Result<Option<LiteralType>, String> {
+        let start = self.current;
+
+        self.match_char('-');
+        match self.peek() {
+            Some('0') => { self.advance(); }
+            Some(c) if c.is_ascii_digit() => self.digits(),
+            _ => return Err(self.expected("a digit")),
+        }
+
+        if self.match_char('.') {
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(self.expected("a digit after '.'"));
+            }
+            self.digits();
+        }
+
+        if self.match_char('e') || self.match_char('E') {
+            if !self.match_char('+') {
+                self.match_char('-');
+            }
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(self.expected("a digit in the exponent"));
+            }
+            self.digits();
+        }
+
+        let text: String = self.chars[start..self.current].iter().collect();
+        return Ok(Some(LiteralType::Number(text.parse().unwrap())));
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.advance();
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Option<LiteralType>) -> Result<Option<LiteralType>, String> {
+        for expected in keyword.chars() {
+            if !self.match_char(expected) {
+                return Err(self.expected(&format!("'{}'", keyword)));
+            }
+        }
+        return Ok(value);
+    }
+
+    fn expected(&self, what: &str) -> String {
+        match self.peek() {
+            Some(c) => format!("expected {} but found {:?}", what, c),
+            None => format!("expected {} but found the end of input", what),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.advance();
+        }
+    }
+
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() != Some(expected) {
+            return false;
+        }
+        self.advance();
+        return true;
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn advance(&mut self) {
+        if self.peek() == Some('\n') {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        self.current += 1;
+    }
+}
+
+fn write_value(json: &mut String, value: &Option<LiteralType>, indent: usize, depth: usize) -> Result<(), String> {
+    match value {
+        None => json.push_str("null"),
+        Some(LiteralType::Boolean(b)) => json.push_str(&b.to_string()),
+        Some(LiteralType::Number(n)) => {
+            if !n.is_finite() {
+                return Err("NaN and infinite numbers can't be converted to JSON".to_owned());
+            }
+            json.push_str(&n.to_string());
+        }
+        Some(LiteralType::String(s)) => write_string(json, s),
+        Some(LiteralType::Tuple(elements)) => {
+            if elements.is_empty() {
+                json.push_str("[]");
+                return Ok(());
+            }
+
+            json.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_newline(json, indent, depth + 1);
+                write_value(json, element, indent, depth + 1)?;
+            }
+            write_newline(json, indent, depth);
+            json.push(']');
+        }
+        Some(LiteralType::Map(map)) => {
+            if map.len() == 0 {
+                json.push_str("{}");
+                return Ok(());
+            }
+
+            json.push('{');
+            for (i, (key, value)) in map.entries().iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_newline(json, indent, depth + 1);
+                write_string(json, key);
+                json.push_str(if indent > 0 { ": " } else { ":" });
+                write_value(json, value, indent, depth + 1)?;
+            }
+            write_newline(json, indent, depth);
+            json.push('}');
+        }
+        Some(LiteralType::LoxCallable(_)) => return Err("functions can't be converted to JSON".to_owned()),
+        Some(other) => return Err(format!("{} values can't be converted to JSON", other.type_name())),
+    }
+    return Ok(());
+}
+
+fn write_newline(json: &mut String, indent: usize, depth: usize) {
+    if indent > 0 {
+        json.push('\n');
+        json.push_str(&" ".repeat(indent * depth));
+    }
+}
+
+fn write_string(json: &mut String, string: &str) {
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '\u{8}' => json.push_str("\\b"),
+            '\u{c}' => json.push_str("\\f"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
@@ -1,28 +1,36 @@
 use std::{rc::Rc, fmt::Display};
-use crate::interpreter::{Interpreter, RuntimeException};
-use crate::token::LiteralType;
+use crate::interpreter::{Interpreter, RuntimeError, RuntimeException};
+use crate::token::{LiteralType, Token};
 use crate::statement::Function;
 use crate::environment::Environment;
+use crate::native::LoxNative;
 
 pub trait LoxCallable {
     fn call(
         &mut self,
         interpreter: &mut Interpreter,
+        paren: &Token,
         arguments: Vec<Option<LiteralType>>,
     ) -> Result<Option<LiteralType>, RuntimeException>;
     fn arity(&self) -> usize;
+
+    // Whether the callable takes any number of arguments after the first
+    // `arity` ones.
+    fn is_variadic(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum LoxCallables {
     LoxFunction(Box<LoxFunction>),
-    LoxAnonymous(Box<LoxAnonymous>),
+    LoxNative(Box<LoxNative>),
 }
 
 impl Display for LoxCallables {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            LoxCallables::LoxAnonymous(_) => write!(f, "<anonymous function>"),
+            LoxCallables::LoxNative(native) => write!(f, "{}", native),
             LoxCallables::LoxFunction(function) => {
                 write!(f, "<fn {}>", function.declaration.name.lexeme)
             }
@@ -34,95 +42,96 @@ impl LoxCallable for LoxCallables {
     fn call(
         &mut self,
         interpreter: &mut Interpreter,
+        paren: &Token,
         arguments: Vec<Option<LiteralType>>,
     ) -> Result<Option<LiteralType>, RuntimeException> {
         match self {
-            LoxCallables::LoxFunction(value) => value.call(interpreter, arguments),
-            LoxCallables::LoxAnonymous(value) => value.call(interpreter, arguments),
+            LoxCallables::LoxFunction(value) => value.call(interpreter, paren, arguments),
+            LoxCallables::LoxNative(value) => value.call(interpreter, paren, arguments),
         }
     }
 
     fn arity(&self) -> usize {
         match self {
             LoxCallables::LoxFunction(value) => value.arity(),
-            LoxCallables::LoxAnonymous(value) => value.arity(),
+            LoxCallables::LoxNative(value) => value.arity(),
         }
     }
-}
-
-#[derive(Clone, PartialEq, Debug)]
-pub struct LoxAnonymous {
-    call_ref: fn(
-        &mut Interpreter,
-        Vec<Option<LiteralType>>,
-    ) -> Result<Option<LiteralType>, RuntimeException>,
-    arity_ref: fn() -> usize,
-}
 
-impl LoxAnonymous {
-    pub fn new(
-        call: fn(
-            &mut Interpreter,
-            Vec<Option<LiteralType>>,
-        ) -> Result<Option<LiteralType>, RuntimeException>,
-        arity: fn() -> usize,
-    ) -> LoxAnonymous {
-        LoxAnonymous {
-            call_ref: call,
-            arity_ref: arity,
+    fn is_variadic(&self) -> bool {
+        match self {
+            LoxCallables::LoxFunction(value) => value.is_variadic(),
+            LoxCallables::LoxNative(value) => value.is_variadic(),
         }
     }
 }
 
-impl LoxCallable for LoxAnonymous {
-    fn call(
-        &mut self,
-        interpreter: &mut Interpreter,
-        arguments: Vec<Option<LiteralType>>,
-    ) -> Result<Option<LiteralType>, RuntimeException> {
-        (self.call_ref)(interpreter, arguments)
-    }
-
-    fn arity(&self) -> usize {
-        (self.arity_ref)()
-    }
-}
-
 #[derive(Clone, PartialEq, Debug)]
 pub struct LoxFunction {
-    declaration: Function,
+    declaration: Rc<Function>,
     closure: Rc<Environment>,
 }
 
 impl LoxFunction {
     pub fn new(declaration: Function, closure: Rc<Environment>) -> LoxFunction {
         LoxFunction {
-            declaration,
+            declaration: Rc::new(declaration),
             closure,
         }
     }
 }
 
+impl LoxFunction {
+    // The scope a call's body runs in, with the parameters bound.
+    fn bind(&self, arguments: Vec<Option<LiteralType>>) -> Rc<Environment> {
+        let environment = Rc::new(Environment::new_call(&self.closure));
+        for i in 0..self.declaration.params.len() {
+            environment.define(
+                self.declaration.params.get(i).unwrap().lexeme.clone(),
+                arguments.get(i).unwrap().clone(),
+            );
+        }
+        return environment;
+    }
+}
+
 impl LoxCallable for LoxFunction {
     fn call(
         &mut self,
         interpreter: &mut Interpreter,
+        paren: &Token,
         arguments: Vec<Option<LiteralType>>,
     ) -> Result<Option<LiteralType>, RuntimeException> {
-        let environment = Rc::new(Environment::new(Some(&self.closure)));
-        for i in 0..self.declaration.params.len() {
-            environment.define(
-                self.declaration.params.get(i).unwrap().lexeme.clone(),
-                arguments.get(i).unwrap().clone(),
-            );
+        if interpreter.stack_exhausted() {
+            return Err(stack_overflow(paren));
         }
+        let mut result = interpreter.execute_block(&self.declaration.body, self.bind(arguments));
 
-        return interpreter
-            .execute_block(&self.declaration.body, environment)
-            .map(|_| None);
+        // Tail calls unwind the body that made them and are run here instead,
+        // so a chain of them never grows the Rust stack.
+        loop {
+            match result {
+                Err(RuntimeException::TailCall(tail_call)) => match tail_call.callee {
+                    LoxCallables::LoxFunction(function) => {
+                        result = interpreter.execute_block(&function.declaration.body, function.bind(tail_call.arguments));
+                    }
+                    mut callee => return callee.call(interpreter, &tail_call.paren, tail_call.arguments),
+                },
+                Err(RuntimeException::Return(r#return)) => return Ok(r#return.value),
+                Err(error) => return Err(error),
+                Ok(()) => return Ok(None),
+            }
+        }
     }
 
     fn arity(&self) -> usize {
         self.declaration.params.len()
     }
 }
+
+// Kept out of `call` so the error doesn't add to the stack every call uses.
+#[cold]
+#[inline(never)]
+fn stack_overflow(paren: &Token) -> RuntimeException {
+    RuntimeException::RuntimeError(RuntimeError::new(paren, "Stack overflow."))
+}
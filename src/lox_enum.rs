@@ -0,0 +1,57 @@
+use std::{fmt::Display, rc::Rc};
+
+#[derive(Debug)]
+pub struct LoxEnum {
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+impl LoxEnum {
+    pub fn new(name: String, variants: Vec<String>) -> Self {
+        Self { name, variants }
+    }
+
+    pub fn variant(enumeration: &Rc<LoxEnum>, name: &str) -> Option<LoxEnumVariant> {
+        enumeration.variants.iter()
+            .position(|variant| variant == name)
+            .map(|ordinal| LoxEnumVariant::new(Rc::clone(enumeration), ordinal))
+    }
+
+    pub fn all_variants(enumeration: &Rc<LoxEnum>) -> Vec<LoxEnumVariant> {
+        (0..enumeration.variants.len())
+            .map(|ordinal| LoxEnumVariant::new(Rc::clone(enumeration), ordinal))
+            .collect()
+    }
+}
+
+// Each declaration is its own enum, even if another has the same name and
+// variants.
+impl PartialEq for LoxEnum {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Display for LoxEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<enum {}>", self.name)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct LoxEnumVariant {
+    pub enumeration: Rc<LoxEnum>,
+    pub ordinal: usize,
+}
+
+impl LoxEnumVariant {
+    pub fn new(enumeration: Rc<LoxEnum>, ordinal: usize) -> Self {
+        Self { enumeration, ordinal }
+    }
+}
+
+impl Display for LoxEnumVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.enumeration.name, self.enumeration.variants[self.ordinal])
+    }
+}
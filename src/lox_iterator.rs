@@ -0,0 +1,44 @@
+use std::{cell::RefCell, fmt::{Debug, Display}, rc::Rc};
+use crate::token::LiteralType;
+
+// A one-shot sequence of values produced on demand, like the lines of
+// standard input. Copies share the position, so a value that has been
+// consumed is gone for all of them.
+#[derive(Clone)]
+pub struct LoxIterator {
+    values: Rc<RefCell<Box<dyn Iterator<Item = Option<LiteralType>>>>>,
+}
+
+impl LoxIterator {
+    pub fn new(values: impl Iterator<Item = Option<LiteralType>> + 'static) -> Self {
+        Self {
+            values: Rc::new(RefCell::new(Box::new(values))),
+        }
+    }
+}
+
+impl Iterator for LoxIterator {
+    type Item = Option<LiteralType>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.values.borrow_mut().next()
+    }
+}
+
+impl PartialEq for LoxIterator {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.values, &other.values)
+    }
+}
+
+impl Debug for LoxIterator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<iterator>")
+    }
+}
+
+impl Display for LoxIterator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<iterator>")
+    }
+}
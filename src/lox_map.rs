@@ -0,0 +1,66 @@
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+use crate::token::{format_element, LiteralType};
+
+// An immutable map from strings to values that remembers the order keys were
+// first inserted in, so printing and iterating it is predictable.
+#[derive(Clone, Debug)]
+pub struct LoxMap {
+    entries: Rc<Vec<(String, Option<LiteralType>)>>,
+    positions: Rc<HashMap<String, usize>>,
+}
+
+impl LoxMap {
+    // Later entries for a key replace the value of earlier ones.
+    pub fn new(entries: Vec<(String, Option<LiteralType>)>) -> Self {
+        let mut unique: Vec<(String, Option<LiteralType>)> = Vec::with_capacity(entries.len());
+        let mut positions: HashMap<String, usize> = HashMap::with_capacity(entries.len());
+        for (key, value) in entries {
+            match positions.get(&key) {
+                Some(&position) => unique[position].1 = value,
+                None => {
+                    positions.insert(key.clone(), unique.len());
+                    unique.push((key, value));
+                }
+            }
+        }
+
+        Self {
+            entries: Rc::new(unique),
+            positions: Rc::new(positions),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Option<LiteralType>> {
+        self.positions.get(key).map(|&position| &self.entries[position].1)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn entries(&self) -> &[(String, Option<LiteralType>)] {
+        &self.entries
+    }
+}
+
+// Maps with the same keys and values are equal whatever order the keys were
+// inserted in.
+impl PartialEq for LoxMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self.entries.iter().all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl Display for LoxMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries: Vec<String> = self.entries.iter()
+            .map(|(key, value)| format!("{:?}: {}", key, format_element(value)))
+            .collect();
+        write!(f, "{{{}}}", entries.join(", "))
+    }
+}
@@ -0,0 +1,35 @@
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+use crate::token::LiteralType;
+
+// A named group of natives, like `math`, whose members are read as
+// properties: `math.sqrt(2)`.
+#[derive(Clone, Debug)]
+pub struct LoxModule {
+    pub name: String,
+    members: Rc<HashMap<String, Option<LiteralType>>>,
+}
+
+impl LoxModule {
+    pub fn new(name: &str, members: HashMap<String, Option<LiteralType>>) -> Self {
+        Self {
+            name: name.to_owned(),
+            members: Rc::new(members),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Option<LiteralType>> {
+        self.members.get(name)
+    }
+}
+
+impl PartialEq for LoxModule {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.members, &other.members)
+    }
+}
+
+impl Display for LoxModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt::Display;
+
+// `start..end` or `start..=end` over integers. Nothing is allocated for the
+// numbers in between; iteration produces them one at a time.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LoxRange {
+    pub start: i64,
+    pub end: i64,
+    pub inclusive: bool,
+}
+
+impl LoxRange {
+    pub fn new(start: i64, end: i64, inclusive: bool) -> Self {
+        Self { start, end, inclusive }
+    }
+
+    // One past the last number in the range. Wider than i64 so an inclusive
+    // range can end at i64::MAX.
+    fn end_exclusive(&self) -> i128 {
+        if self.inclusive { self.end as i128 + 1 } else { self.end as i128 }
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        value.fract() == 0.0 && value >= self.start as f64 && value <= self.end as f64
+            && (self.inclusive || value != self.end as f64)
+    }
+
+    pub fn len(&self) -> usize {
+        let len = (self.end_exclusive() - self.start as i128).max(0);
+        usize::try_from(len).unwrap_or(usize::MAX)
+    }
+
+    // Taken from `start..=end` rather than `start..end_exclusive()`, which
+    // wouldn't fit in an i64 for a range ending at i64::MAX.
+    pub fn iter(&self) -> impl Iterator<Item = i64> {
+        (self.start..=self.end).take(self.len())
+    }
+
+    // The indices a slice with this range covers in a sequence of `length`
+    // items, or None if it is reversed or reaches outside the sequence.
+    pub fn slice_bounds(&self, length: usize) -> Option<(usize, usize)> {
+        let end = self.end_exclusive();
+        if self.start < 0 || self.end < self.start || end > length as i128 {
+            return None;
+        }
+        return Some((self.start as usize, end as usize));
+    }
+}
+
+impl Display for LoxRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = if self.inclusive { "..=" } else { ".." };
+        write!(f, "{}{}{}", self.start, operator, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoxRange;
+
+    // Range expressions only allow bounds between -2^53 and 2^53.
+    const LIMIT: i64 = 1 << 53;
+
+    #[test]
+    fn inclusive_ranges_can_end_at_the_largest_bound() {
+        let range = LoxRange::new(LIMIT - 2, LIMIT, true);
+        assert_eq!(range.len(), 3);
+        assert_eq!(range.iter().collect::<Vec<_>>(), vec![LIMIT - 2, LIMIT - 1, LIMIT]);
+        assert_eq!(range.slice_bounds(10), None);
+        assert!(range.contains(LIMIT as f64));
+        assert!(!range.contains(0.0));
+    }
+
+    #[test]
+    fn exclusive_ranges_can_end_at_the_smallest_bound() {
+        let range = LoxRange::new(-LIMIT, -LIMIT, false);
+        assert_eq!(range.len(), 0);
+        assert_eq!(range.iter().count(), 0);
+        assert!(!range.contains(-LIMIT as f64));
+    }
+
+    #[test]
+    fn the_widest_range_has_a_length() {
+        assert_eq!(LoxRange::new(-LIMIT, LIMIT, true).len(), (1 << 54) + 1);
+        assert_eq!(LoxRange::new(-LIMIT, LIMIT, false).len(), 1 << 54);
+    }
+
+    #[test]
+    fn slices_must_fit_the_sequence() {
+        assert_eq!(LoxRange::new(1, 3, false).slice_bounds(3), Some((1, 3)));
+        assert_eq!(LoxRange::new(3, 3, false).slice_bounds(3), Some((3, 3)));
+        assert_eq!(LoxRange::new(1, 3, true).slice_bounds(3), None);
+        assert_eq!(LoxRange::new(-1, 2, false).slice_bounds(3), None);
+        assert_eq!(LoxRange::new(0, LIMIT, true).slice_bounds(3), None);
+    }
+
+    #[test]
+    fn reversed_slices_are_rejected_either_way() {
+        assert_eq!(LoxRange::new(3, 2, false).slice_bounds(3), None);
+        assert_eq!(LoxRange::new(3, 2, true).slice_bounds(3), None);
+        assert_eq!(LoxRange::new(2, 2, true).slice_bounds(3), Some((2, 3)));
+    }
+}
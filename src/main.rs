@@ -7,11 +7,31 @@ mod interpreter;
 mod statement;
 mod environment;
 mod lox_callable;
+mod fiber;
+mod lox_enum;
+mod byte_string;
+mod lox_range;
+mod native;
+mod time;
+mod string;
+mod math;
+mod file_system;
+mod input;
+mod lox_iterator;
+mod types;
+mod lox_map;
+mod lox_module;
+mod json;
+mod regex_natives;
+mod process;
+mod random;
+mod format;
 
 use std::env;
 use std::fs;
 use std::io::{self, Write};
 use std::process::exit;
+use std::thread;
 
 use scanner::Scanner;
 use parser::Parser;
@@ -22,6 +42,11 @@ use token::TokenType;
 use expression::Expr;
 use interpreter::Interpreter;
 use interpreter::RuntimeError;
+use time::FixedClock;
+
+// Lox calls recurse on the Rust stack, so scripts run on a thread with room
+// for deep recursion. The interpreter stops them before it runs out.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
 
 static mut HAD_ERROR: bool = false;
 static mut HAD_RUNTIME_ERROR: bool = false;
@@ -48,10 +73,68 @@ fn report(line: usize, location: &str, message: String) {
     eprintln!("[line {}] Error{}: {}", line, location, message);
 }
 
+// `exit` skips the buffered output's destructor, so flush it first.
+fn exit_with(code: i32) -> ! {
+    io::stdout().flush().unwrap();
+    exit(code);
+}
+
+// Flags given before the command, e.g. `--time=2024-05-01T12:00:00Z run script.lox`.
+struct Options {
+    // Pins the time scripts see, so their output doesn't depend on when they run.
+    time: Option<f64>,
+    // Disables natives untrusted scripts shouldn't have; see `check_sandbox`.
+    sandbox: bool,
+    // Makes the random number natives repeat the same sequence every run.
+    seed: Option<u64>,
+}
+
+impl Options {
+    // Takes the flags off the front of `args`, leaving the program name, the
+    // command and what follows it.
+    fn parse(args: &mut Vec<String>) -> Result<Self, String> {
+        let mut options = Self { time: None, sandbox: false, seed: None };
+
+        while args.len() > 1 && args[1].starts_with("--") {
+            let flag = args.remove(1);
+            let (name, value) = flag.split_once('=').unwrap_or((&flag, ""));
+            match name {
+                "--time" => match time::parse_iso8601(value) {
+                    Some(time) => options.time = Some(time),
+                    None => return Err(format!("--time: '{}' is not an ISO-8601 timestamp", value)),
+                },
+                "--seed" => match value.parse::<i64>() {
+                    Ok(seed) => options.seed = Some(seed as u64),
+                    Err(_) => return Err(format!("--seed: '{}' is not an integer", value)),
+                },
+                "--sandbox" if name == flag => options.sandbox = true,
+                _ => return Err(format!("Unknown flag: {}", flag)),
+            }
+        }
+
+        return Ok(options);
+    }
+}
+
 struct Lox {
+    options: Options,
 }
 
 impl Lox {
+    fn interpreter(&self, args: &[String]) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_stack_size(STACK_SIZE);
+        interpreter.set_args(args[3..].to_vec());
+        interpreter.set_sandboxed(self.options.sandbox);
+        if let Some(seed) = self.options.seed {
+            interpreter.random().seed(seed);
+        }
+        if let Some(time) = self.options.time {
+            interpreter.set_clock(Box::new(FixedClock::new(time)));
+        }
+        return interpreter;
+    }
+
     fn run_file(&self, args: &Vec<String>) {
         let command = &args[1];
         let filename = &args[2];
@@ -85,8 +168,11 @@ impl Lox {
                     exit(65);
                 }
 
-                let mut interpreter = Interpreter::new();
-                interpreter.interpret_expression(expr.as_ref().unwrap());
+                let mut interpreter = self.interpreter(args);
+                let code = interpreter.interpret_expression(expr.as_ref().unwrap());
+                if let Some(code) = code {
+                    exit_with(code);
+                }
 
                 if unsafe { HAD_RUNTIME_ERROR } {
                     exit(70);
@@ -99,8 +185,11 @@ impl Lox {
                     exit(65);
                 }
 
-                let mut interpreter = Interpreter::new();
-                interpreter.interpret(statements);
+                let mut interpreter = self.interpreter(args);
+                let code = interpreter.interpret(statements);
+                if let Some(code) = code {
+                    exit_with(code);
+                }
 
                 if unsafe { HAD_RUNTIME_ERROR } {
                     exit(70);
@@ -156,12 +245,27 @@ impl Lox {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let lox = thread::Builder::new().stack_size(STACK_SIZE).spawn(run).unwrap();
+    if let Err(panic) = lox.join() {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+fn run() {
+    let mut args: Vec<String> = env::args().collect();
+    let options = match Options::parse(&mut args) {
+        Ok(options) => options,
+        Err(message) => {
+            writeln!(io::stderr(), "{}", message).unwrap();
+            exit(64);
+        }
+    };
+
     if args.len() < 3 {
-        writeln!(io::stderr(), "Usage: {} tokenize <filename>", args[0]).unwrap();
+        writeln!(io::stderr(), "Usage: {} [--time=<timestamp>] [--sandbox] [--seed=<n>] tokenize <filename> [arguments...]", args[0]).unwrap();
         return;
     }
 
-    let lox = Lox {};
+    let lox = Lox { options };
     lox.run_file(&args);
 }
@@ -0,0 +1,53 @@
+use std::f64::consts;
+use crate::interpreter::Interpreter;
+use crate::native::{check_number, NativeResult};
+use crate::token::{LiteralType, Token};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.define_global("PI", Some(LiteralType::Number(consts::PI)));
+    interpreter.define_global("E", Some(LiteralType::Number(consts::E)));
+    interpreter.define_global("INF", Some(LiteralType::Number(f64::INFINITY)));
+    interpreter.define_global("NAN", Some(LiteralType::Number(f64::NAN)));
+
+    interpreter.register_native("sqrt", 1, unary("sqrt", f64::sqrt));
+    interpreter.register_native("abs", 1, unary("abs", f64::abs));
+    interpreter.register_native("floor", 1, unary("floor", f64::floor));
+    interpreter.register_native("ceil", 1, unary("ceil", f64::ceil));
+    interpreter.register_native("round", 1, unary("round", f64::round));
+    interpreter.register_native("sin", 1, unary("sin", f64::sin));
+    interpreter.register_native("cos", 1, unary("cos", f64::cos));
+    interpreter.register_native("tan", 1, unary("tan", f64::tan));
+    interpreter.register_native("log", 1, unary("log", f64::ln));
+    interpreter.register_native("exp", 1, unary("exp", f64::exp));
+    interpreter.register_native("pow", 2, binary("pow", f64::powf));
+    interpreter.register_native("min", 2, binary("min", f64::min));
+    interpreter.register_native("max", 2, binary("max", f64::max));
+    interpreter.register_native("atan2", 2, binary("atan2", f64::atan2));
+
+    interpreter.register_native("isNan", 1, predicate("isNan", f64::is_nan));
+    interpreter.register_native("isFinite", 1, predicate("isFinite", f64::is_finite));
+}
+
+// Adapters from plain f64 functions to natives. Results follow IEEE 754, so
+// `sqrt(-1)` is NaN rather than an error.
+fn unary(name: &'static str, function: fn(f64) -> f64) -> impl FnMut(&mut Interpreter, &Token, Vec<Option<LiteralType>>) -> NativeResult {
+    move |_interpreter, paren, arguments| {
+        let x = check_number(paren, name, "argument", &arguments[0])?;
+        return Ok(Some(LiteralType::Number(function(x))));
+    }
+}
+
+fn binary(name: &'static str, function: fn(f64, f64) -> f64) -> impl FnMut(&mut Interpreter, &Token, Vec<Option<LiteralType>>) -> NativeResult {
+    move |_interpreter, paren, arguments| {
+        let x = check_number(paren, name, "first argument", &arguments[0])?;
+        let y = check_number(paren, name, "second argument", &arguments[1])?;
+        return Ok(Some(LiteralType::Number(function(x, y))));
+    }
+}
+
+fn predicate(name: &'static str, function: fn(f64) -> bool) -> impl FnMut(&mut Interpreter, &Token, Vec<Option<LiteralType>>) -> NativeResult {
+    move |_interpreter, paren, arguments| {
+        let x = check_number(paren, name, "argument", &arguments[0])?;
+        return Ok(Some(LiteralType::Boolean(function(x))));
+    }
+}
@@ -0,0 +1,144 @@
+// Functions implemented in Rust and callable from Lox.
+//
+// Natives live in modules grouped by what they do (time, fiber, byte_string,
+// ...), each with a `register` function that adds its natives to an
+// interpreter. `Interpreter::register_module` gathers what one of those adds
+// into a module object, so scripts can call `math.sqrt(2)` as well as the
+// global `sqrt(2)`. Code embedding the interpreter adds its own natives
+// through `Interpreter::register_native`, or its own modules the same way.
+use std::{cell::RefCell, fmt::{Debug, Display}, rc::Rc};
+use crate::interpreter::{Interpreter, RuntimeError, RuntimeException};
+use crate::lox_callable::LoxCallable;
+use crate::token::{LiteralType, Token};
+
+pub type NativeResult = Result<Option<LiteralType>, RuntimeException>;
+
+// The longest string, in bytes, a native builds from a length it was given,
+// so a runaway count is an error rather than exhausting memory.
+pub const MAX_STRING_LENGTH: usize = 1 << 28;
+pub type NativeFn = dyn Fn(&mut Interpreter, &Token, Vec<Option<LiteralType>>) -> NativeResult;
+
+#[derive(Clone)]
+pub struct LoxNative {
+    name: String,
+    arity: usize,
+    variadic: bool,
+    function: Rc<NativeFn>,
+}
+
+impl LoxNative {
+    pub fn new(name: &str, arity: usize, function: Rc<NativeFn>) -> Self {
+        Self {
+            name: name.to_owned(),
+            arity,
+            variadic: false,
+            function,
+        }
+    }
+
+    // Lets calls pass extra arguments after the first `arity` ones.
+    pub fn variadic(mut self) -> Self {
+        self.variadic = true;
+        return self;
+    }
+
+    // A native with its own mutable state. It can't be called again while a
+    // call to it is still running, e.g. from a Lox function it called back
+    // into; that is reported as a runtime error.
+    pub fn new_mut<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: FnMut(&mut Interpreter, &Token, Vec<Option<LiteralType>>) -> NativeResult + 'static,
+    {
+        let function = RefCell::new(function);
+        let native_name = name.to_owned();
+        return Self::new(name, arity, Rc::new(move |interpreter, paren, arguments| {
+            match function.try_borrow_mut() {
+                Ok(mut function) => return function(interpreter, paren, arguments),
+                Err(_) => return Err(error(paren, &format!("{}: called again before its previous call returned.", native_name))),
+            }
+        }));
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl PartialEq for LoxNative {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.function, &other.function)
+    }
+}
+
+impl Debug for LoxNative {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+impl Display for LoxNative {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn>")
+    }
+}
+
+impl LoxCallable for LoxNative {
+    fn call(
+        &mut self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        arguments: Vec<Option<LiteralType>>,
+    ) -> Result<Option<LiteralType>, RuntimeException> {
+        (self.function)(interpreter, paren, arguments)
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn is_variadic(&self) -> bool {
+        self.variadic
+    }
+}
+
+pub fn error(paren: &Token, message: &str) -> RuntimeException {
+    RuntimeException::RuntimeError(RuntimeError::new(paren, message))
+}
+
+// Natives that reach outside the script call this first. A sandboxed script
+// can still read files and exit, but can't write or remove files, read the
+// environment or run commands.
+pub fn check_sandbox(interpreter: &Interpreter, paren: &Token, function: &str) -> Result<(), RuntimeException> {
+    if interpreter.sandboxed() {
+        return Err(error(paren, &format!("{}: not available in the sandbox.", function)));
+    }
+    return Ok(());
+}
+
+pub fn strings_tuple<'a>(strings: impl Iterator<Item = &'a str>) -> NativeResult {
+    let elements = strings.map(|string| Some(LiteralType::String(string.to_owned()))).collect();
+    return Ok(Some(LiteralType::Tuple(Rc::new(elements))));
+}
+
+// Argument checks shared by the native modules. `function` and `argument`
+// name the native and the parameter in the error message.
+pub fn check_string(paren: &Token, function: &str, argument: &str, value: &Option<LiteralType>) -> Result<String, RuntimeException> {
+    match value {
+        Some(LiteralType::String(string)) => return Ok(string.clone()),
+        _ => return Err(error(paren, &format!("{}: {} must be a string.", function, argument))),
+    }
+}
+
+pub fn check_number(paren: &Token, function: &str, argument: &str, value: &Option<LiteralType>) -> Result<f64, RuntimeException> {
+    match value {
+        Some(LiteralType::Number(number)) => return Ok(*number),
+        _ => return Err(error(paren, &format!("{}: {} must be a number.", function, argument))),
+    }
+}
+
+pub fn check_integer(paren: &Token, function: &str, argument: &str, value: &Option<LiteralType>) -> Result<i64, RuntimeException> {
+    match value {
+        Some(LiteralType::Number(number)) if number.fract() == 0.0 => return Ok(*number as i64),
+        _ => return Err(error(paren, &format!("{}: {} must be an integer.", function, argument))),
+    }
+}
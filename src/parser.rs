@@ -1,4 +1,4 @@
-use crate::{error_token, statement::{Statement, Print, Expression, Var, Block, If, While, Function, Return}, expression::*, token::*};
+use crate::{error_token, statement::{Statement, Print, Expression, Var, Block, If, While, Function, Return, Assert, Enum, ForIn, Destructure, Defer, Break, Continue}, expression::*, token::*};
 
 pub struct ParseError {}
 
@@ -11,6 +11,9 @@ impl ParseError {
 pub struct Parser {
     tokens: Box<[Token]>,
     current: usize,
+    // Labels of the loops around the statement being parsed, innermost last;
+    // None for an unlabeled loop.
+    loops: Vec<Option<Token>>,
 }
 
 impl Parser {
@@ -18,6 +21,7 @@ impl Parser {
         Self {
             tokens,
             current: 0,
+            loops: Vec::new(),
         }
     }
 
@@ -47,9 +51,34 @@ impl Parser {
             return Ok(self.var_declaration()?);
         }
 
+        if self.match_single(&TokenType::Enum) {
+            return Ok(self.enum_declaration()?);
+        }
+
         return Ok(self.statement()?);
     }
 
+    fn enum_declaration(&mut self) -> Result<Statement, ParseError> {
+        let name = self.consume(&TokenType::Identifier, "Expect enum name.")?.clone();
+        self.consume(&TokenType::LeftBrace, "Expect '{' before enum body.")?;
+
+        let mut variants: Vec<Token> = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            let variant = self.consume(&TokenType::Identifier, "Expect variant name.")?.clone();
+            if variants.iter().any(|v| v.lexeme == variant.lexeme) {
+                self.error(&variant, format!("Duplicate variant '{}' in enum.", variant.lexeme));
+            }
+            variants.push(variant);
+
+            if !self.match_single(&TokenType::Comma) {
+                break;
+            }
+        }
+
+        self.consume(&TokenType::RightBrace, "Expect '}' after enum body.")?;
+        return Ok(Statement::Enum(Enum::new(name, variants)));
+    }
+
     fn function(&mut self, kind: String) -> Result<Statement, ParseError> {
         let name = self.consume(&TokenType::Identifier, &format!("Expect {kind} name."))?.clone();
         
@@ -75,13 +104,21 @@ impl Parser {
 
         self.consume(&TokenType::RightParen, "Expect ')' after parameters.")?;
         self.consume(&TokenType::LeftBrace, &format!("Expect '{{' before {kind} body."))?;
-        
-        let body = self.block()?;
+
+        // `break` and `continue` can't reach loops outside the function.
+        let enclosing_loops = std::mem::take(&mut self.loops);
+        let body = self.block();
+        self.loops = enclosing_loops;
+        let body = body?;
 
         return Ok(Statement::Function(Function::new(name, parameters, body)));
     }
 
     fn var_declaration(&mut self) -> Result<Statement, ParseError> {
+        if self.match_single(&TokenType::LeftParen) {
+            return self.destructuring_declaration();
+        }
+
         let name = self.consume(&TokenType::Identifier, "Expect variable name.")?.clone();
 
         let mut initializer: Option<Expr> = None;
@@ -94,7 +131,30 @@ impl Parser {
         return Ok(Statement::Var(Var::new(name, initializer)));
     }
 
+    fn destructuring_declaration(&mut self) -> Result<Statement, ParseError> {
+        let paren = self.previous().clone();
+
+        let mut names: Vec<Token> = Vec::new();
+        loop {
+            names.push(self.consume(&TokenType::Identifier, "Expect variable name.")?.clone());
+            if !self.match_single(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(&TokenType::RightParen, "Expect ')' after variable names.")?;
+
+        self.consume(&TokenType::Equal, "Expect '=' after destructuring pattern.")?;
+        let initializer = self.expression()?;
+        self.consume(&TokenType::Semicolon, "Expect ';' after variable declaration.")?;
+
+        return Ok(Statement::Destructure(Destructure::new(names, paren, initializer)));
+    }
+
     fn statement(&mut self) -> Result<Statement, ParseError> {
+        if self.check(&TokenType::Identifier) && self.check_ahead(1, &TokenType::Colon) {
+            return self.labeled_statement();
+        }
+
         if self.match_single(&TokenType::If) {
             return Ok(self.if_statement()?);
         }
@@ -108,17 +168,35 @@ impl Parser {
         }
         
         if self.match_single(&TokenType::While) {
-            return Ok(self.while_statement()?);
+            return Ok(self.while_statement(None)?);
         }
 
         if self.match_single(&TokenType::For) {
-            return Ok(self.for_statement()?);
+            return Ok(self.for_statement(None)?);
+        }
+
+        if self.match_single(&TokenType::Break) {
+            let (keyword, label) = self.loop_jump()?;
+            return Ok(Statement::Break(Break::new(keyword, label)));
+        }
+
+        if self.match_single(&TokenType::Continue) {
+            let (keyword, label) = self.loop_jump()?;
+            return Ok(Statement::Continue(Continue::new(keyword, label)));
         }
 
         if self.match_single(&TokenType::Return) {
             return Ok(self.return_statement()?);
         }
 
+        if self.match_single(&TokenType::Assert) {
+            return Ok(self.assert_statement()?);
+        }
+
+        if self.match_single(&TokenType::Defer) {
+            return Ok(Statement::Defer(Defer::new(self.statement()?)));
+        }
+
         return Ok(self.expression_statement()?);
     }
 
@@ -153,17 +231,69 @@ impl Parser {
         return Ok(statements);
     }
 
-    fn while_statement(&mut self) -> Result<Statement, ParseError> {
+    // `label: while (...) ...` or `label: for (...) ...`.
+    fn labeled_statement(&mut self) -> Result<Statement, ParseError> {
+        let label = self.advance().clone();
+        self.advance();
+
+        if self.loops.iter().flatten().any(|enclosing| enclosing.lexeme == label.lexeme) {
+            self.error(&label, format!("Label '{}' is already used by an enclosing loop.", label.lexeme));
+        }
+
+        if self.match_single(&TokenType::While) {
+            return self.while_statement(Some(label));
+        }
+
+        if self.match_single(&TokenType::For) {
+            return self.for_statement(Some(label));
+        }
+
+        return Err(self.error(&label, "Labels can only be applied to loops.".to_string()));
+    }
+
+    // The keyword and optional label of a `break` or `continue`.
+    fn loop_jump(&mut self) -> Result<(Token, Option<Token>), ParseError> {
+        let keyword = self.previous().clone();
+
+        let mut label = None;
+        if self.match_single(&TokenType::Identifier) {
+            label = Some(self.previous().clone());
+        }
+        self.consume(&TokenType::Semicolon, &format!("Expect ';' after '{}'.", keyword.lexeme))?;
+
+        if self.loops.is_empty() {
+            self.error(&keyword, format!("Can't use '{}' outside of a loop.", keyword.lexeme));
+        } else if let Some(label) = &label {
+            if !self.loops.iter().flatten().any(|enclosing| enclosing.lexeme == label.lexeme) {
+                self.error(label, format!("Undefined label '{}'.", label.lexeme));
+            }
+        }
+
+        return Ok((keyword, label));
+    }
+
+    fn loop_body(&mut self, label: &Option<Token>) -> Result<Statement, ParseError> {
+        self.loops.push(label.clone());
+        let body = self.statement();
+        self.loops.pop();
+        return body;
+    }
+
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Statement, ParseError> {
         self.consume(&TokenType::LeftParen, "Expect '(' after 'while'.")?;
         let condition = self.expression()?;
         self.consume(&TokenType::RightParen, "Expect ')' after condition.")?;
-        let body = self.statement()?;
-        return Ok(Statement::While(While::new(condition, body)));
+        let body = self.loop_body(&label)?;
+        return Ok(Statement::While(While::new(condition, body, None, label)));
     }
 
-    fn for_statement(&mut self) -> Result<Statement, ParseError> {
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Statement, ParseError> {
         self.consume(&TokenType::LeftParen, "Expect '(' after 'for'.")?;
 
+        if self.check(&TokenType::Var) && self.check_ahead(2, &TokenType::In) {
+            return self.for_in_statement(label);
+        }
+
         let mut initializer: Option<Statement> = None;
         if self.match_single(&TokenType::Semicolon) {
             // no initializer was declared inside
@@ -185,18 +315,15 @@ impl Parser {
         }
         self.consume(&TokenType::RightParen, "Expect ')' after for clauses.")?;
 
-        let mut body = self.statement()?;
-
-        if increment.is_some() {
-            body = Statement::Block(Block::new(vec![
-                body,
-                Statement::Expression(Expression::new(increment.unwrap())),
-            ]));
-        }
+        let body = self.loop_body(&label)?;
 
+        // The increment stays on the loop rather than being appended to the
+        // body, so that `continue` doesn't skip it.
         let mut while_statement = Statement::While(While::new(
             condition.unwrap_or(Expr::Literal(Literal::new(Some(LiteralType::Boolean(true))))),
             body,
+            increment,
+            label,
         ));
 
         if initializer.is_some() {
@@ -206,16 +333,53 @@ impl Parser {
         return Ok(while_statement);
     }
 
+    fn for_in_statement(&mut self, label: Option<Token>) -> Result<Statement, ParseError> {
+        self.consume(&TokenType::Var, "Expect 'var' in for-in loop.")?;
+        let name = self.consume(&TokenType::Identifier, "Expect variable name.")?.clone();
+        self.consume(&TokenType::In, "Expect 'in' after loop variable.")?;
+        let iterable = self.expression()?;
+        self.consume(&TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let body = self.loop_body(&label)?;
+        return Ok(Statement::ForIn(ForIn::new(name, iterable, body, label)));
+    }
+
     fn return_statement(&mut self) -> Result<Statement, ParseError> {
         let keyword = self.previous().clone();
         let mut value = None;
         if !self.check(&TokenType::Semicolon) {
-            value = Some(self.expression()?);
+            let first = self.expression()?;
+
+            // `return a, b;` returns the tuple (a, b).
+            if self.check(&TokenType::Comma) {
+                let mut elements = vec![first];
+                while self.match_single(&TokenType::Comma) {
+                    elements.push(self.expression()?);
+                }
+                value = Some(Expr::Tuple(Tuple::new(elements)));
+            } else {
+                value = Some(first);
+            }
         }
         self.consume(&TokenType::Semicolon, "Expect ';' after return value.")?;
         return Ok(Statement::Return(Return::new(keyword, value)));
     }
 
+    fn assert_statement(&mut self) -> Result<Statement, ParseError> {
+        let keyword = self.previous().clone();
+
+        let start = self.current;
+        let condition = self.expression()?;
+        let source = self.source_text(start, self.current);
+
+        let mut message = None;
+        if self.match_single(&TokenType::Comma) {
+            message = Some(self.expression()?);
+        }
+        self.consume(&TokenType::Semicolon, "Expect ';' after assertion.")?;
+        return Ok(Statement::Assert(Assert::new(keyword, condition, message, source)));
+    }
+
     fn expression_statement(&mut self) -> Result<Statement, ParseError> {
         let expression = self.expression()?;
         self.consume(&TokenType::Semicolon, "Expect ';' after expression.")?;
@@ -227,7 +391,7 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Result<Expr, ParseError> {
-        let expr = self.or()?;
+        let expr = self.pipeline()?;
 
         if self.match_single(&TokenType::Equal) {
             let equals = self.previous().clone();
@@ -246,6 +410,39 @@ impl Parser {
         return Ok(expr);
     }
 
+    // `x |> f(a)` is sugar for `f(x, a)`, and `x |> f` for `f(x)`.
+    fn pipeline(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.coalesce()?;
+
+        while self.match_single(&TokenType::Pipe) {
+            let pipe = self.previous().clone();
+            let right = self.coalesce()?;
+
+            expr = match right {
+                Expr::Call(call) => {
+                    let mut arguments = call.arguments;
+                    arguments.insert(0, expr);
+                    Expr::Call(Call::new(*call.callee, call.paren, arguments, call.optional).piped())
+                }
+                callee => Expr::Call(Call::new(callee, pipe, vec![expr], false).piped()),
+            };
+        }
+
+        return Ok(expr);
+    }
+
+    fn coalesce(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.or()?;
+
+        while self.match_single(&TokenType::QuestionQuestion) {
+            let operator = self.previous().clone();
+            let right = self.or()?;
+            expr = Expr::Logical(Logical::new(expr, operator, right));
+        }
+
+        return Ok(expr);
+    }
+
     fn or(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.and()?;
         
@@ -284,11 +481,11 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Result<Expr, ParseError> {
-        let mut expr = self.term()?;
+        let mut expr = self.range()?;
 
-        while self.match_many(&vec![TokenType::Greater, TokenType::GreaterEqual, TokenType::Less, TokenType::LessEqual]) {
+        while self.match_many(&vec![TokenType::Greater, TokenType::GreaterEqual, TokenType::Less, TokenType::LessEqual, TokenType::In]) {
             let operator = self.previous().clone();
-            let right = self.term()?;
+            let right = self.range()?;
 
             expr = Expr::Binary(Binary::new(expr, operator, right));
         }
@@ -296,6 +493,19 @@ impl Parser {
         return Ok(expr);
     }
 
+    // Ranges don't chain: `1..2..3` is an error rather than a range of ranges.
+    fn range(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.term()?;
+
+        if self.match_many(&vec![TokenType::DotDot, TokenType::DotDotEqual]) {
+            let operator = self.previous().clone();
+            let end = self.term()?;
+            return Ok(Expr::Range(Range::new(expr, operator, end)));
+        }
+
+        return Ok(expr);
+    }
+
     fn term(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.factor()?;
 
@@ -337,7 +547,25 @@ impl Parser {
 
         loop {
             if self.match_single(&TokenType::LeftParen) {
-                expr = self.finish_call(expr)?;
+                expr = self.finish_call(expr, false)?;
+            }
+            else if self.match_single(&TokenType::Dot) {
+                let name = self.consume(&TokenType::Identifier, "Expect property name after '.'.")?.clone();
+                expr = Expr::Get(Get::new(expr, name, false));
+            }
+            else if self.match_single(&TokenType::LeftBracket) {
+                let bracket = self.previous().clone();
+                let index = self.expression()?;
+                self.consume(&TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = Expr::Index(Index::new(expr, bracket, index));
+            }
+            else if self.match_single(&TokenType::QuestionDot) {
+                if self.match_single(&TokenType::LeftParen) {
+                    expr = self.finish_call(expr, true)?;
+                } else {
+                    let name = self.consume(&TokenType::Identifier, "Expect property name or '(' after '?.'.")?.clone();
+                    expr = Expr::Get(Get::new(expr, name, true));
+                }
             }
             else {
                 break;
@@ -347,7 +575,7 @@ impl Parser {
         return Ok(expr);
     }
 
-    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+    fn finish_call(&mut self, callee: Expr, optional: bool) -> Result<Expr, ParseError> {
         let mut arguments: Vec<Expr> = Vec::new();
         if !self.check(&TokenType::RightParen) {
             loop {
@@ -363,7 +591,7 @@ impl Parser {
 
         let paren = self.consume(&TokenType::RightParen, "Expect ')' after arguments.")?.clone();
 
-        return Ok(Expr::Call(Call::new(callee, paren, arguments)));
+        return Ok(Expr::Call(Call::new(callee, paren, arguments, optional)));
     }
 
     fn primary(&mut self) -> Result<Expr, ParseError> {
@@ -379,12 +607,19 @@ impl Parser {
             return Ok(Expr::Literal(Literal::new(None)));
         }
 
-        if self.match_many(&vec![TokenType::Number, TokenType::String]) {
+        if self.match_many(&vec![TokenType::Number, TokenType::String, TokenType::Bytes]) {
             return Ok(Expr::Literal(Literal::new(self.previous().literal.clone())));
         }
         
         if self.match_single(&TokenType::LeftParen) {
+            if self.match_single(&TokenType::RightParen) {
+                return Ok(Expr::Tuple(Tuple::new(Vec::new())));
+            }
+
             let expr = self.expression()?;
+            if self.match_single(&TokenType::Comma) {
+                return self.finish_tuple(expr);
+            }
             self.consume(&TokenType::RightParen, "Expect ')' after expression.")?;
             return Ok(Expr::Grouping(Grouping::new(expr)));
         }
@@ -396,6 +631,19 @@ impl Parser {
         return Err(self.error(self.peek(), "Expect expression.".to_string()));
     }
 
+    fn finish_tuple(&mut self, first: Expr) -> Result<Expr, ParseError> {
+        let mut elements = vec![first];
+        while !self.check(&TokenType::RightParen) {
+            elements.push(self.expression()?);
+            if !self.match_single(&TokenType::Comma) {
+                break;
+            }
+        }
+
+        self.consume(&TokenType::RightParen, "Expect ')' after tuple elements.")?;
+        return Ok(Expr::Tuple(Tuple::new(elements)));
+    }
+
     fn consume(&mut self, token_type: &TokenType, message: &str) -> Result<&Token, ParseError> {
         if self.check(&token_type) {
             return Ok(self.advance());
@@ -429,6 +677,13 @@ impl Parser {
         return self.peek().token_type == *token_type;
     }
 
+    fn check_ahead(&self, offset: usize, token_type: &TokenType) -> bool {
+        match self.tokens.get(self.current + offset) {
+            Some(token) => return token.token_type == *token_type,
+            None => return false,
+        }
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -448,6 +703,49 @@ impl Parser {
         &self.tokens[self.current - 1]
     }
 
+    // Rebuilds the source text of a run of tokens from their lexemes, with
+    // spacing normalized.
+    fn source_text(&self, start: usize, end: usize) -> String {
+        let mut text = String::new();
+        for i in start..end {
+            let token = &self.tokens[i];
+            if i > start && self.space_between(start, i) {
+                text.push(' ');
+            }
+            text.push_str(&token.lexeme);
+        }
+        return text;
+    }
+
+    fn space_between(&self, start: usize, i: usize) -> bool {
+        let previous = &self.tokens[i - 1];
+        let token = &self.tokens[i];
+
+        match previous.token_type {
+            TokenType::LeftParen | TokenType::LeftBracket | TokenType::Dot | TokenType::QuestionDot | TokenType::Bang
+            | TokenType::DotDot | TokenType::DotDotEqual => return false,
+            // A minus that doesn't follow an operand is unary.
+            TokenType::Minus if i - 1 == start || !self.is_operand_end(&self.tokens[i - 2]) => return false,
+            _ => {}
+        }
+
+        match token.token_type {
+            TokenType::RightParen | TokenType::RightBracket | TokenType::Comma | TokenType::Dot | TokenType::QuestionDot
+            | TokenType::DotDot | TokenType::DotDotEqual => return false,
+            TokenType::LeftParen | TokenType::LeftBracket => return !self.is_operand_end(previous),
+            _ => return true,
+        }
+    }
+
+    fn is_operand_end(&self, token: &Token) -> bool {
+        match token.token_type {
+            TokenType::Identifier | TokenType::Number | TokenType::String | TokenType::Bytes
+            | TokenType::RightParen | TokenType::RightBracket
+            | TokenType::True | TokenType::False | TokenType::Nil | TokenType::This => return true,
+            _ => return false,
+        }
+    }
+
     fn error(&self, token: &Token, message: String) -> ParseError {
         error_token(token, message);
         return ParseError::new();
@@ -463,13 +761,18 @@ impl Parser {
 
             match self.peek().token_type {
                 TokenType::Class
+                | TokenType::Enum
                 | TokenType::Fun
                 | TokenType::Var
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::Return => return,
+                | TokenType::Return
+                | TokenType::Assert
+                | TokenType::Defer
+                | TokenType::Break
+                | TokenType::Continue => return,
                 _ => { self.advance(); }
             }
         }
@@ -0,0 +1,244 @@
+// Natives for the script's process: its arguments, environment and exit
+// code, and running other programs.
+use std::env;
+use std::io::{ErrorKind, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use bytes::Bytes;
+use crate::interpreter::{Interpreter, RuntimeException};
+use crate::lox_map::LoxMap;
+use crate::native::{check_integer, check_number, check_sandbox, check_string, error, strings_tuple, NativeResult};
+use crate::token::{LiteralType, Token};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("args", 0, args);
+    interpreter.register_native("getenv", 1, getenv);
+    interpreter.register_native("exit", 1, exit);
+    interpreter.register_variadic_native("exec", 2, exec);
+}
+
+// How long exec waits for the rest of a command's output once it has exited.
+const OUTPUT_GRACE: Duration = Duration::from_millis(250);
+
+// The command-line arguments after the script's path.
+pub fn args(interpreter: &mut Interpreter, _paren: &Token, _arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    return strings_tuple(interpreter.args().iter().map(|arg| arg.as_str()));
+}
+
+// nil if the variable isn't set or isn't valid Unicode.
+pub fn getenv(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    check_sandbox(interpreter, paren, "getenv")?;
+    let name = check_string(paren, "getenv", "name", &arguments[0])?;
+    if name.is_empty() || name.contains(['=', '\0']) {
+        return Err(error(paren, "getenv: name must not be empty or contain '=' or NUL."));
+    }
+
+    match env::var(&name) {
+        Ok(value) => return Ok(Some(LiteralType::String(value))),
+        Err(_) => return Ok(None),
+    }
+}
+
+// Unwinds the whole script, running deferred statements on the way out, and
+// ends the process with `code`.
+pub fn exit(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let code = check_integer(paren, "exit", "code", &arguments[0])?;
+    if !(0..=255).contains(&code) {
+        return Err(error(paren, "exit: code must be between 0 and 255."));
+    }
+    return Err(RuntimeException::Exit(code as i32));
+}
+
+// `exec(command, arguments, options)` runs `command` with `arguments`, a
+// tuple of strings or nil for none, and waits for it to finish. The optional
+// `options` is nil, a map or a tuple of (name, value) pairs like
+// `(("cwd", "build"), ("timeout", 60))`, with any of:
+//   stdin    a string or byte string to write to the command's input; without
+//            it the command reads the script's own standard input
+//   cwd      the directory to run the command in
+//   timeout  seconds to wait before killing the command, which is an error
+// Returns a map with the command's `stdout` and `stderr`, decoded as UTF-8,
+// and its exit `status`, which is nil if a signal ended it.
+pub fn exec(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    check_sandbox(interpreter, paren, "exec")?;
+    if arguments.len() > 3 {
+        return Err(error(paren, &format!("exec: expected at most 3 arguments but got {}.", arguments.len())));
+    }
+
+    let command = check_string(paren, "exec", "command", &arguments[0])?;
+    let command_arguments = match &arguments[1] {
+        None => Vec::new(),
+        Some(LiteralType::Tuple(elements)) => {
+            let mut strings = Vec::new();
+            for element in elements.iter() {
+                strings.push(check_string(paren, "exec", "each argument", element)?);
+            }
+            strings
+        }
+        _ => return Err(error(paren, "exec: arguments must be a tuple of strings.")),
+    };
+    let options = ExecOptions::new(paren, arguments.get(2).unwrap_or(&None))?;
+
+    let mut process = Command::new(&command);
+    process.args(&command_arguments).stdout(Stdio::piped()).stderr(Stdio::piped());
+    if options.stdin.is_some() {
+        process.stdin(Stdio::piped());
+    }
+    if let Some(cwd) = &options.cwd {
+        process.current_dir(cwd);
+    }
+
+    let mut child = match process.spawn() {
+        Ok(child) => child,
+        Err(e) => return Err(error(paren, &format!("exec: {}: {}.", command, e))),
+    };
+
+    // Input and output each get a thread, so a command blocked writing one
+    // pipe can't deadlock with us blocked on another.
+    if let (Some(input), Some(mut stdin)) = (options.stdin, child.stdin.take()) {
+        thread::spawn(move || {
+            // The command may exit without reading all of it.
+            let _ = stdin.write_all(&input);
+        });
+    }
+    let (sender, receiver) = mpsc::channel();
+    read_chunks(child.stdout.take().unwrap(), Stream::Stdout, sender.clone());
+    read_chunks(child.stderr.take().unwrap(), Stream::Stderr, sender);
+
+    let status = match options.timeout {
+        None => child.wait().map(Some),
+        Some(timeout) => wait_with_timeout(&mut child, timeout),
+    };
+    let status = match status {
+        Ok(Some(status)) => status,
+        // The output threads stop once they find nobody is listening.
+        Ok(None) => return Err(error(paren, &format!("exec: {} timed out after {}s.", command, options.timeout.unwrap().as_secs_f64()))),
+        Err(e) => return Err(error(paren, &format!("exec: {}: {}.", command, e))),
+    };
+
+    let (stdout, stderr) = collect_output(&receiver);
+    let output = |bytes: Vec<u8>| Some(LiteralType::String(String::from_utf8_lossy(&bytes).into_owned()));
+    return Ok(Some(LiteralType::Map(LoxMap::new(vec![
+        ("stdout".to_owned(), output(stdout)),
+        ("stderr".to_owned(), output(stderr)),
+        ("status".to_owned(), status.code().map(|code| LiteralType::Number(code as f64))),
+    ]))));
+}
+
+struct ExecOptions {
+    stdin: Option<Bytes>,
+    cwd: Option<String>,
+    timeout: Option<Duration>,
+}
+
+impl ExecOptions {
+    fn new(paren: &Token, value: &Option<LiteralType>) -> Result<Self, RuntimeException> {
+        let mut options = Self { stdin: None, cwd: None, timeout: None };
+        let entries = match value {
+            None => return Ok(options),
+            Some(LiteralType::Map(map)) => map.entries().to_vec(),
+            Some(LiteralType::Tuple(pairs)) => {
+                let mut entries = Vec::new();
+                for pair in pairs.iter() {
+                    match pair {
+                        Some(LiteralType::Tuple(pair)) if pair.len() == 2 => {
+                            entries.push((check_string(paren, "exec", "option name", &pair[0])?, pair[1].clone()));
+                        }
+                        _ => return Err(error(paren, "exec: options must be (name, value) pairs.")),
+                    }
+                }
+                entries
+            }
+            _ => return Err(error(paren, "exec: options must be a map, a tuple of pairs or nil.")),
+        };
+
+        for (key, value) in &entries {
+            if value.is_none() {
+                continue;
+            }
+            match key.as_str() {
+                "stdin" => match value {
+                    Some(LiteralType::String(string)) => options.stdin = Some(Bytes::from(string.clone())),
+                    Some(LiteralType::Bytes(bytes)) => options.stdin = Some(bytes.clone()),
+                    _ => return Err(error(paren, "exec: stdin must be a string or byte string.")),
+                },
+                "cwd" => options.cwd = Some(check_string(paren, "exec", "cwd", value)?),
+                "timeout" => {
+                    let seconds = check_number(paren, "exec", "timeout", value)?;
+                    match Duration::try_from_secs_f64(seconds) {
+                        Ok(timeout) if seconds > 0.0 => options.timeout = Some(timeout),
+                        _ => return Err(error(paren, "exec: timeout must be a positive number of seconds.")),
+                    }
+                }
+                _ => return Err(error(paren, &format!("exec: unknown option '{}'.", key))),
+            }
+        }
+
+        return Ok(options);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+// Reads `pipe` on a thread of its own, sending on what it reads as it comes.
+fn read_chunks(mut pipe: impl Read + Send + 'static, stream: Stream, sender: Sender<(Stream, Vec<u8>)>) {
+    thread::spawn(move || {
+        let mut buffer = [0; 8192];
+        loop {
+            match pipe.read(&mut buffer) {
+                Ok(0) => return,
+                Ok(read) => {
+                    if sender.send((stream, buffer[..read].to_vec())).is_err() {
+                        return;
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return,
+            }
+        }
+    });
+}
+
+// The stdout and stderr of a command that has exited. Its pipes normally
+// close with it, but something it left running in the background can hold
+// them open indefinitely, so we wait at most OUTPUT_GRACE for the end.
+fn collect_output(receiver: &Receiver<(Stream, Vec<u8>)>) -> (Vec<u8>, Vec<u8>) {
+    let deadline = Instant::now() + OUTPUT_GRACE;
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    // Ends when both readers are done, or at the deadline once nothing
+    // already read is left.
+    while let Ok((stream, chunk)) = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        match stream {
+            Stream::Stdout => stdout.extend(chunk),
+            Stream::Stderr => stderr.extend(chunk),
+        }
+    }
+    return (stdout, stderr);
+}
+
+// None if the child was killed for running past `timeout`.
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> std::io::Result<Option<ExitStatus>> {
+    let deadline = match Instant::now().checked_add(timeout) {
+        Some(deadline) => deadline,
+        // Too far off to represent, which is as good as never.
+        None => return child.wait().map(Some),
+    };
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
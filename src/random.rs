@@ -0,0 +1,132 @@
+// Pseudo-random numbers. Each interpreter has its own generator, seeded from
+// the operating system unless a script calls `seed(n)` or the interpreter is
+// started with `--seed=<n>`, which makes every run produce the same numbers.
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::rc::Rc;
+use crate::interpreter::Interpreter;
+use crate::native::{check_integer, error, NativeResult};
+use crate::token::{LiteralType, Token};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("random", 0, random);
+    interpreter.register_native("randomInt", 2, random_int);
+    interpreter.register_native("shuffle", 1, shuffle);
+    interpreter.register_native("choice", 1, choice);
+    interpreter.register_native("seed", 1, seed);
+}
+
+// xoshiro256**: fast, small and good enough for anything but cryptography.
+pub struct Random {
+    state: [u64; 4],
+}
+
+impl Random {
+    pub fn new() -> Self {
+        // Hash maps are keyed with fresh randomness from the OS.
+        let seed = RandomState::new().build_hasher().finish();
+        return Self::from_seed(seed);
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
+        let mut random = Self { state: [0; 4] };
+        random.seed(seed);
+        return random;
+    }
+
+    // Spreads the seed over the whole state with SplitMix64, as the
+    // xoshiro authors recommend, so similar seeds give unrelated sequences.
+    pub fn seed(&mut self, seed: u64) {
+        let mut x = seed;
+        for word in self.state.iter_mut() {
+            x = x.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            *word = z ^ (z >> 31);
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        return result;
+    }
+
+    // In [0, 1), using the top 53 bits so every result is equally likely.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    // In [0, bound). Draws that would favour the low numbers are thrown away.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let x = self.next_u64();
+            if x >= threshold {
+                return x % bound;
+            }
+        }
+    }
+}
+
+pub fn random(interpreter: &mut Interpreter, _paren: &Token, _arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    return Ok(Some(LiteralType::Number(interpreter.random().next_f64())));
+}
+
+// Both bounds are included, so `randomInt(1, 6)` rolls a die.
+pub fn random_int(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let low = check_integer(paren, "randomInt", "low", &arguments[0])?;
+    let high = check_integer(paren, "randomInt", "high", &arguments[1])?;
+    if low > high {
+        return Err(error(paren, "randomInt: low must not be greater than high."));
+    }
+
+    // Only the whole range of 64-bit integers has more numbers than fit in a u64.
+    let offset = match high.abs_diff(low).checked_add(1) {
+        Some(span) => interpreter.random().below(span),
+        None => interpreter.random().next_u64(),
+    };
+    return Ok(Some(LiteralType::Number(low.wrapping_add(offset as i64) as f64)));
+}
+
+// A new tuple with the same elements in a random order.
+pub fn shuffle(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let mut elements = match &arguments[0] {
+        Some(LiteralType::Tuple(elements)) => elements.as_ref().clone(),
+        _ => return Err(error(paren, "shuffle: argument must be a tuple.")),
+    };
+
+    for i in (1..elements.len()).rev() {
+        let j = interpreter.random().below(i as u64 + 1) as usize;
+        elements.swap(i, j);
+    }
+    return Ok(Some(LiteralType::Tuple(Rc::new(elements))));
+}
+
+pub fn choice(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    match &arguments[0] {
+        Some(LiteralType::Tuple(elements)) if elements.is_empty() => {
+            return Err(error(paren, "choice: can't choose from an empty tuple."));
+        }
+        Some(LiteralType::Tuple(elements)) => {
+            let i = interpreter.random().below(elements.len() as u64) as usize;
+            return Ok(elements[i].clone());
+        }
+        _ => return Err(error(paren, "choice: argument must be a tuple.")),
+    }
+}
+
+pub fn seed(interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let seed = check_integer(paren, "seed", "argument", &arguments[0])?;
+    interpreter.random().seed(seed as u64);
+    return Ok(None);
+}
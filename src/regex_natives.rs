@@ -0,0 +1,148 @@
+// Regular expression natives, using the syntax of the `regex` crate.
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use regex::Regex;
+use crate::interpreter::{Interpreter, RuntimeException};
+use crate::native::{check_string, error, strings_tuple, NativeResult};
+use crate::token::{LiteralType, Token};
+
+// How many compiled patterns an interpreter keeps.
+const MAX_PATTERNS: usize = 64;
+
+// Compiled patterns, shared by the natives of one interpreter so that a
+// pattern used in a loop is only compiled once. Past MAX_PATTERNS the least
+// recently used one is dropped, so scripts building patterns on the fly
+// don't keep every one of them.
+struct PatternCache {
+    patterns: HashMap<String, (Regex, u64)>,
+    // Counts lookups, to stamp each pattern with when it was last used.
+    uses: u64,
+}
+
+impl PatternCache {
+    fn new() -> Self {
+        Self {
+            patterns: HashMap::new(),
+            uses: 0,
+        }
+    }
+
+    fn get(&mut self, pattern: &str) -> Option<Regex> {
+        self.uses += 1;
+        let (regex, last_used) = self.patterns.get_mut(pattern)?;
+        *last_used = self.uses;
+        return Some(regex.clone());
+    }
+
+    fn insert(&mut self, pattern: String, regex: Regex) {
+        if self.patterns.len() >= MAX_PATTERNS {
+            let oldest = self.patterns.iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(pattern, _)| pattern.clone());
+            if let Some(oldest) = oldest {
+                self.patterns.remove(&oldest);
+            }
+        }
+        self.uses += 1;
+        self.patterns.insert(pattern, (regex, self.uses));
+    }
+}
+
+pub fn register(interpreter: &mut Interpreter) {
+    let cache = Rc::new(RefCell::new(PatternCache::new()));
+
+    let patterns = Rc::clone(&cache);
+    interpreter.register_native("match", 2, move |_interpreter, paren, arguments| {
+        let regex = compile(&patterns, paren, "match", &arguments[0])?;
+        let text = check_string(paren, "match", "text", &arguments[1])?;
+        return match_groups(&regex, &text);
+    });
+
+    let patterns = Rc::clone(&cache);
+    interpreter.register_native("findAll", 2, move |_interpreter, paren, arguments| {
+        let regex = compile(&patterns, paren, "findAll", &arguments[0])?;
+        let text = check_string(paren, "findAll", "text", &arguments[1])?;
+        return strings_tuple(regex.find_iter(&text).map(|m| m.as_str()));
+    });
+
+    let patterns = Rc::clone(&cache);
+    interpreter.register_native("replaceAll", 3, move |_interpreter, paren, arguments| {
+        let regex = compile(&patterns, paren, "replaceAll", &arguments[0])?;
+        let text = check_string(paren, "replaceAll", "text", &arguments[1])?;
+        let replacement = check_string(paren, "replaceAll", "replacement", &arguments[2])?;
+        return Ok(Some(LiteralType::String(regex.replace_all(&text, replacement.as_str()).into_owned())));
+    });
+
+    // Only `regex.split`: the global `split` splits on a plain separator.
+    let patterns = cache;
+    interpreter.module_only(|interpreter| {
+        interpreter.register_native("split", 2, move |_interpreter, paren, arguments| {
+            let regex = compile(&patterns, paren, "split", &arguments[0])?;
+            let text = check_string(paren, "split", "text", &arguments[1])?;
+            return strings_tuple(regex.split(&text));
+        });
+    });
+}
+
+fn compile(cache: &RefCell<PatternCache>, paren: &Token, function: &str, pattern: &Option<LiteralType>) -> Result<Regex, RuntimeException> {
+    let pattern = check_string(paren, function, "pattern", pattern)?;
+    if let Some(regex) = cache.borrow_mut().get(&pattern) {
+        return Ok(regex);
+    }
+
+    match Regex::new(&pattern) {
+        Ok(regex) => {
+            cache.borrow_mut().insert(pattern, regex.clone());
+            return Ok(regex);
+        }
+        Err(e) => return Err(error(paren, &format!("{}: invalid pattern: {}", function, e))),
+    }
+}
+
+// The first match as a tuple of the whole match followed by each capture
+// group, with nil for groups that didn't take part; nil if nothing matches.
+fn match_groups(regex: &Regex, text: &str) -> NativeResult {
+    match regex.captures(text) {
+        Some(captures) => {
+            let groups = captures.iter()
+                .map(|group| group.map(|group| LiteralType::String(group.as_str().to_owned())))
+                .collect();
+            return Ok(Some(LiteralType::Tuple(Rc::new(groups))));
+        }
+        None => return Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use super::{PatternCache, MAX_PATTERNS};
+
+    #[test]
+    fn the_cache_drops_the_least_recently_used_pattern() {
+        let mut cache = PatternCache::new();
+        for i in 0..MAX_PATTERNS {
+            let pattern = format!("a{{{}}}", i);
+            cache.insert(pattern.clone(), Regex::new(&pattern).unwrap());
+        }
+        // Using the first pattern again leaves the second as the oldest.
+        assert!(cache.get("a{0}").is_some());
+
+        cache.insert("b".to_owned(), Regex::new("b").unwrap());
+        assert_eq!(cache.patterns.len(), MAX_PATTERNS);
+        assert!(cache.get("a{0}").is_some());
+        assert!(cache.get("a{1}").is_none());
+        assert!(cache.get("b").is_some());
+    }
+
+    #[test]
+    fn the_cache_never_grows_past_its_limit() {
+        let mut cache = PatternCache::new();
+        for i in 0..MAX_PATTERNS * 10 {
+            let pattern = i.to_string();
+            if cache.get(&pattern).is_none() {
+                cache.insert(pattern.clone(), Regex::new(&pattern).unwrap());
+            }
+        }
+        assert_eq!(cache.patterns.len(), MAX_PATTERNS);
+    }
+}
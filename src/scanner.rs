@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use crate::{error, token::{Token, TokenType}};
+use bytes::{BufMut, BytesMut};
+
+use crate::{byte_string, error, token::{Token, TokenType}};
 
 pub struct Scanner {
     source: Vec<char>,
@@ -21,12 +23,18 @@ impl Scanner {
             line: 1,
             keywords: HashMap::from([
                 ("and", TokenType::And),
+                ("assert", TokenType::Assert),
+                ("break", TokenType::Break),
                 ("class", TokenType::Class),
+                ("continue", TokenType::Continue),
+                ("defer", TokenType::Defer),
                 ("else", TokenType::Else),
+                ("enum", TokenType::Enum),
                 ("false", TokenType::False),
                 ("for", TokenType::For),
                 ("fun", TokenType::Fun),
                 ("if", TokenType::If),
+                ("in", TokenType::In),
                 ("nil", TokenType::Nil),
                 ("or", TokenType::Or),
                 ("print", TokenType::Print),
@@ -56,8 +64,18 @@ impl Scanner {
             ')' => self.add_token(TokenType::RightParen, None),
             '{' => self.add_token(TokenType::LeftBrace, None),
             '}' => self.add_token(TokenType::RightBrace, None),
+            '[' => self.add_token(TokenType::LeftBracket, None),
+            ']' => self.add_token(TokenType::RightBracket, None),
             ',' => self.add_token(TokenType::Comma, None),
-            '.' => self.add_token(TokenType::Dot, None),
+            ':' => self.add_token(TokenType::Colon, None),
+            '.' => {
+                if self.match_char('.') {
+                    let token_type = if self.match_char('=') { TokenType::DotDotEqual } else { TokenType::DotDot };
+                    self.add_token(token_type, None);
+                } else {
+                    self.add_token(TokenType::Dot, None);
+                }
+            }
             '-' => self.add_token(TokenType::Minus, None),
             '+' => self.add_token(TokenType::Plus, None),
             '*' => self.add_token(TokenType::Star, None),
@@ -78,6 +96,22 @@ impl Scanner {
                 let token_type = if self.match_char('=') { TokenType::GreaterEqual } else { TokenType::Greater };
                 self.add_token(token_type, None);
             }
+            '|' => {
+                if self.match_char('>') {
+                    self.add_token(TokenType::Pipe, None);
+                } else {
+                    error(self.line, format!("Unexpected character: {}", c));
+                }
+            }
+            '?' => {
+                if self.match_char('?') {
+                    self.add_token(TokenType::QuestionQuestion, None);
+                } else if self.match_char('.') {
+                    self.add_token(TokenType::QuestionDot, None);
+                } else {
+                    error(self.line, format!("Unexpected character: {}", c));
+                }
+            }
             '/' => {
                 if self.match_char('/') {
                     while self.peek() != '\n' && !self.is_at_end() {
@@ -89,9 +123,19 @@ impl Scanner {
             }
             ' ' | '\r' | '\t' => {}
             '\n' => { self.line += 1 }
-            '"' => { self.string(); }
+            '"' => {
+                if self.peek() == '"' && self.peek_next() == '"' {
+                    self.triple_quoted_string();
+                } else {
+                    self.string();
+                }
+            }
             _ => {
-                if self.is_digit(c) {
+                if c == 'r' && (self.peek() == '"' || self.peek() == '#') {
+                    self.raw_string();
+                } else if c == 'b' && self.peek() == '"' {
+                    self.byte_string();
+                } else if self.is_digit(c) {
                     self.number();
                 } else if self.is_alpha(c) {
                     self.identifier();
@@ -118,6 +162,11 @@ impl Scanner {
         self.tokens.push(Token::new_number(token_type, text, literal, self.line));
     }
 
+    fn add_token_bytes(&mut self, literal: BytesMut) {
+        let text: String = self.source[self.start..self.current].into_iter().collect();
+        self.tokens.push(Token::new_bytes(TokenType::Bytes, text, literal.freeze(), self.line));
+    }
+
     fn is_at_end(&self) -> bool {
         return self.current >= self.source.len();
     }
@@ -145,11 +194,15 @@ impl Scanner {
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
+        return self.peek_at(1);
+    }
+
+    fn peek_at(&self, offset: usize) -> char {
+        if self.current + offset >= self.source.len() {
             return '\0';
         }
 
-        return self.source[self.current + 1];
+        return self.source[self.current + offset];
     }
 
     fn string(&mut self) {
@@ -171,6 +224,171 @@ impl Scanner {
         self.add_token(TokenType::String, Some(value));
     }
 
+    // r"..." keeps its contents verbatim. Any number of '#' may follow the 'r'
+    // (r#"..."#) so that the string itself can contain double quotes.
+    fn raw_string(&mut self) {
+        let mut hashes = 0;
+        while self.peek() == '#' {
+            hashes += 1;
+            self.advance();
+        }
+
+        if !self.match_char('"') {
+            // Not a raw string after all (e.g. `r#x`); scan `r` as an identifier.
+            self.current = self.start + 1;
+            self.identifier();
+            return;
+        }
+
+        let content_start = self.current;
+        loop {
+            if self.is_at_end() {
+                error(self.line, "Unterminated string.".to_string());
+                return;
+            }
+
+            let c = self.advance();
+            if c == '\n' {
+                self.line += 1;
+            } else if c == '"' && self.closes_raw_string(hashes) {
+                break;
+            }
+        }
+
+        let value = self.source[content_start..(self.current - 1)].into_iter().collect();
+        self.current += hashes;
+        self.add_token(TokenType::String, Some(value));
+    }
+
+    // b"..." holds bytes rather than text. Besides literal characters (stored
+    // as UTF-8) it understands the escapes \xHH, \n, \r, \t, \0, \\ and \".
+    fn byte_string(&mut self) {
+        self.advance();
+
+        let mut value = BytesMut::new();
+        while self.peek() != '"' && !self.is_at_end() {
+            let c = self.advance();
+            match c {
+                '\\' => self.byte_escape(&mut value),
+                _ => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    let mut buffer = [0; 4];
+                    value.put_slice(c.encode_utf8(&mut buffer).as_bytes());
+                }
+            }
+        }
+
+        if self.is_at_end() {
+            error(self.line, "Unterminated string.".to_string());
+            return;
+        }
+
+        self.advance();
+        self.add_token_bytes(value);
+    }
+
+    fn byte_escape(&mut self, value: &mut BytesMut) {
+        let byte = match self.advance_if_not_at_end() {
+            'n' => Some(b'\n'),
+            'r' => Some(b'\r'),
+            't' => Some(b'\t'),
+            '0' => Some(0),
+            '\\' => Some(b'\\'),
+            '"' => Some(b'"'),
+            'x' => {
+                let high = self.hex_digit();
+                let low = high.and_then(|_| self.hex_digit());
+                match (high, low) {
+                    (Some(high), Some(low)) => Some((high << 4) | low),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        match byte {
+            Some(byte) => value.put_u8(byte),
+            None => error(self.line, "Invalid escape sequence in byte string.".to_string()),
+        }
+    }
+
+    // Only consumes the next character if it is a hex digit, so a short
+    // `\x` escape doesn't swallow the closing quote.
+    fn hex_digit(&mut self) -> Option<u8> {
+        let value = byte_string::hex_digit_value(self.peek());
+        if value.is_some() {
+            self.advance();
+        }
+        return value;
+    }
+
+    fn advance_if_not_at_end(&mut self) -> char {
+        if self.is_at_end() {
+            return '\0';
+        }
+        return self.advance();
+    }
+
+    fn closes_raw_string(&self, hashes: usize) -> bool {
+        if self.current + hashes > self.source.len() {
+            return false;
+        }
+
+        return self.source[self.current..(self.current + hashes)].iter().all(|c| *c == '#');
+    }
+
+    // """...""" may span lines and contain lone quotes. The common indentation
+    // of its lines is stripped, as are a leading and a trailing blank line, so
+    // the literal can be indented along with the surrounding code.
+    fn triple_quoted_string(&mut self) {
+        self.advance();
+        self.advance();
+
+        let content_start = self.current;
+        while !self.is_at_end() && !(self.peek() == '"' && self.peek_next() == '"' && self.peek_at(2) == '"') {
+            if self.peek() == '\n' {
+                self.line += 1;
+            }
+            self.advance();
+        }
+
+        if self.is_at_end() {
+            error(self.line, "Unterminated string.".to_string());
+            return;
+        }
+
+        let content: String = self.source[content_start..self.current].into_iter().collect();
+        self.current += 3;
+
+        let value = self.strip_common_indent(&content);
+        self.add_token(TokenType::String, Some(value));
+    }
+
+    fn strip_common_indent(&self, content: &str) -> String {
+        let mut lines: Vec<&str> = content.split('\n').collect();
+
+        if lines.len() > 1 && lines[0].trim().is_empty() {
+            lines.remove(0);
+        }
+
+        if lines.len() > 1 && lines[lines.len() - 1].trim().is_empty() {
+            lines.pop();
+        }
+
+        let indent = lines.iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.chars().take_while(|c| *c == ' ' || *c == '\t').count())
+            .min()
+            .unwrap_or(0);
+
+        return lines.iter()
+            .map(|line| line.chars().skip(indent).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n");
+    }
+
     fn is_digit(&self, c: char) -> bool {
         return c >= '0' && c <= '9';
     }
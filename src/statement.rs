@@ -1,3 +1,4 @@
+use std::rc::Rc;
 use crate::{expression::Expr, token::Token};
 
 #[derive(Clone, PartialEq, Debug)]
@@ -41,6 +42,24 @@ impl Var {
     }
 }
 
+// `var (a, b) = tuple;`
+#[derive(Clone, PartialEq, Debug)]
+pub struct Destructure {
+    pub names: Vec<Token>,
+    pub paren: Token,
+    pub initializer: Box<Expr>,
+}
+
+impl Destructure {
+    pub fn new(names: Vec<Token>, paren: Token, initializer: Expr) -> Self {
+        Self {
+            names,
+            paren,
+            initializer: Box::new(initializer),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct Block {
     pub statements: Vec<Statement>,
@@ -73,13 +92,19 @@ impl If {
 pub struct While {
     pub condition: Box<Expr>,
     pub body: Box<Statement>,
+    // The increment clause of a desugared `for`, run after the body even when
+    // the body continues.
+    pub increment: Option<Box<Expr>>,
+    pub label: Option<Token>,
 }
 
 impl While {
-    pub fn new(condition: Expr, body: Statement) -> Self {
+    pub fn new(condition: Expr, body: Statement, increment: Option<Expr>, label: Option<Token>) -> Self {
         Self {
             condition: Box::new(condition),
             body: Box::new(body),
+            increment: increment.map(Box::new),
+            label,
         }
     }
 }
@@ -111,16 +136,111 @@ impl Return {
     }
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct Assert {
+    pub keyword: Token,
+    pub condition: Box<Expr>,
+    pub message: Option<Box<Expr>>,
+    pub source: String,
+}
+
+impl Assert {
+    pub fn new(keyword: Token, condition: Expr, message: Option<Expr>, source: String) -> Self {
+        Self {
+            keyword,
+            condition: Box::new(condition),
+            message: message.map(|m| Box::new(m)),
+            source,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Enum {
+    pub name: Token,
+    pub variants: Vec<Token>,
+}
+
+impl Enum {
+    pub fn new(name: Token, variants: Vec<Token>) -> Self {
+        Self { name, variants }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ForIn {
+    pub name: Token,
+    pub iterable: Box<Expr>,
+    pub body: Box<Statement>,
+    pub label: Option<Token>,
+}
+
+impl ForIn {
+    pub fn new(name: Token, iterable: Expr, body: Statement, label: Option<Token>) -> Self {
+        Self {
+            name,
+            iterable: Box::new(iterable),
+            body: Box::new(body),
+            label,
+        }
+    }
+}
+
+// `break;` or `break label;`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Break {
+    pub keyword: Token,
+    pub label: Option<Token>,
+}
+
+impl Break {
+    pub fn new(keyword: Token, label: Option<Token>) -> Self {
+        Self { keyword, label }
+    }
+}
+
+// `continue;` or `continue label;`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Continue {
+    pub keyword: Token,
+    pub label: Option<Token>,
+}
+
+impl Continue {
+    pub fn new(keyword: Token, label: Option<Token>) -> Self {
+        Self { keyword, label }
+    }
+}
+
+// `defer stmt;` runs `stmt` when the enclosing block or function exits.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Defer {
+    pub statement: Rc<Statement>,
+}
+
+impl Defer {
+    pub fn new(statement: Statement) -> Self {
+        Self { statement: Rc::new(statement) }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum Statement {
     Print(Print),
     Expression(Expression),
     Var(Var),
+    Destructure(Destructure),
     Block(Block),
     If(If),
     While(While),
     Function(Function),
     Return(Return),
+    Assert(Assert),
+    Enum(Enum),
+    ForIn(ForIn),
+    Defer(Defer),
+    Break(Break),
+    Continue(Continue),
 }
 
 impl Statement {
@@ -129,11 +249,18 @@ impl Statement {
             Statement::Print(print) => visitor.visit_print(print),
             Statement::Expression(expression) => visitor.visit_expression(expression),
             Statement::Var(var) => visitor.visit_var(var),
+            Statement::Destructure(destructure) => visitor.visit_destructure(destructure),
             Statement::Block(block) => visitor.visit_block(block),
             Statement::If(if_statement) => visitor.visit_if(if_statement),
             Statement::While(while_statement) => visitor.visit_while(while_statement),
             Statement::Function(function_statement) => visitor.visit_function(function_statement),
             Statement::Return(return_statement) => visitor.visit_return(return_statement),
+            Statement::Assert(assert_statement) => visitor.visit_assert(assert_statement),
+            Statement::Enum(enum_statement) => visitor.visit_enum(enum_statement),
+            Statement::ForIn(for_in_statement) => visitor.visit_for_in(for_in_statement),
+            Statement::Defer(defer_statement) => visitor.visit_defer(defer_statement),
+            Statement::Break(break_statement) => visitor.visit_break(break_statement),
+            Statement::Continue(continue_statement) => visitor.visit_continue(continue_statement),
         };
     }
 }
@@ -143,9 +270,16 @@ pub trait Visitor {
     fn visit_print(&mut self, print: &Print) -> Self::Output;
     fn visit_expression(&mut self, expression: &Expression) -> Self::Output;
     fn visit_var(&mut self, var: &Var) -> Self::Output;
+    fn visit_destructure(&mut self, destructure: &Destructure) -> Self::Output;
     fn visit_block(&mut self, block: &Block) -> Self::Output;
     fn visit_if(&mut self, if_statement: &If) -> Self::Output;
     fn visit_while(&mut self, while_statement: &While) -> Self::Output;
     fn visit_function(&mut self, function_statement: &Function) -> Self::Output;
     fn visit_return(&mut self, return_statement: &Return) -> Self::Output;
+    fn visit_assert(&mut self, assert_statement: &Assert) -> Self::Output;
+    fn visit_enum(&mut self, enum_statement: &Enum) -> Self::Output;
+    fn visit_for_in(&mut self, for_in_statement: &ForIn) -> Self::Output;
+    fn visit_defer(&mut self, defer_statement: &Defer) -> Self::Output;
+    fn visit_break(&mut self, break_statement: &Break) -> Self::Output;
+    fn visit_continue(&mut self, continue_statement: &Continue) -> Self::Output;
 }
\ No newline at end of file
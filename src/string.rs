@@ -0,0 +1,140 @@
+// String natives. Positions and lengths count code points, not bytes, so
+// "héllo" has length 5 and "l" is at index 2.
+use std::rc::Rc;
+use crate::interpreter::Interpreter;
+use crate::native::{check_integer, check_string, error, strings_tuple, NativeResult, MAX_STRING_LENGTH};
+use crate::token::{LiteralType, Token};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("len", 1, len);
+    interpreter.register_native("substring", 3, substring);
+    interpreter.register_native("indexOf", 2, index_of);
+    interpreter.register_native("split", 2, split);
+    interpreter.register_native("join", 2, join);
+    interpreter.register_native("trim", 1, trim);
+    interpreter.register_native("upper", 1, upper);
+    interpreter.register_native("lower", 1, lower);
+    interpreter.register_native("replace", 3, replace);
+    interpreter.register_native("startsWith", 2, starts_with);
+    interpreter.register_native("endsWith", 2, ends_with);
+    interpreter.register_native("repeat", 2, repeat);
+    interpreter.register_native("chars", 1, chars);
+}
+
+fn string_value(string: String) -> NativeResult {
+    return Ok(Some(LiteralType::String(string)));
+}
+
+// Works on every value that has a length, not only strings.
+pub fn len(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let length = match &arguments[0] {
+        Some(LiteralType::String(string)) => string.chars().count(),
+        Some(LiteralType::Bytes(bytes)) => bytes.len(),
+        Some(LiteralType::Tuple(elements)) => elements.len(),
+        Some(LiteralType::Range(range)) => range.len(),
+        Some(LiteralType::Map(map)) => map.len(),
+        _ => return Err(error(paren, "len: argument must be a string, byte string, tuple, range or map.")),
+    };
+    return Ok(Some(LiteralType::Number(length as f64)));
+}
+
+pub fn substring(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "substring", "string", &arguments[0])?;
+    let start = check_integer(paren, "substring", "start", &arguments[1])?;
+    let end = check_integer(paren, "substring", "end", &arguments[2])?;
+
+    let length = string.chars().count() as i64;
+    if start < 0 || end > length || start > end {
+        return Err(error(paren, &format!("substring: {}..{} is out of range for length {}.", start, end, length)));
+    }
+    return string_value(string.chars().skip(start as usize).take((end - start) as usize).collect());
+}
+
+pub fn index_of(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "indexOf", "string", &arguments[0])?;
+    let search = check_string(paren, "indexOf", "search string", &arguments[1])?;
+
+    let index = match string.find(&search) {
+        Some(byte_index) => string[..byte_index].chars().count() as f64,
+        None => -1.0,
+    };
+    return Ok(Some(LiteralType::Number(index)));
+}
+
+pub fn split(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "split", "string", &arguments[0])?;
+    let separator = check_string(paren, "split", "separator", &arguments[1])?;
+    if separator.is_empty() {
+        return Err(error(paren, "split: separator must not be empty."));
+    }
+    return strings_tuple(string.split(separator.as_str()));
+}
+
+pub fn join(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let elements = match &arguments[0] {
+        Some(LiteralType::Tuple(elements)) => Rc::clone(elements),
+        _ => return Err(error(paren, "join: first argument must be a tuple.")),
+    };
+    let separator = check_string(paren, "join", "separator", &arguments[1])?;
+
+    let mut strings = Vec::with_capacity(elements.len());
+    for element in elements.iter() {
+        strings.push(check_string(paren, "join", "every element", element)?);
+    }
+    return string_value(strings.join(&separator));
+}
+
+pub fn trim(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "trim", "argument", &arguments[0])?;
+    return string_value(string.trim().to_owned());
+}
+
+pub fn upper(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "upper", "argument", &arguments[0])?;
+    return string_value(string.to_uppercase());
+}
+
+pub fn lower(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "lower", "argument", &arguments[0])?;
+    return string_value(string.to_lowercase());
+}
+
+pub fn replace(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "replace", "string", &arguments[0])?;
+    let from = check_string(paren, "replace", "search string", &arguments[1])?;
+    let to = check_string(paren, "replace", "replacement", &arguments[2])?;
+    if from.is_empty() {
+        return Err(error(paren, "replace: search string must not be empty."));
+    }
+    return string_value(string.replace(&from, &to));
+}
+
+pub fn starts_with(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "startsWith", "string", &arguments[0])?;
+    let prefix = check_string(paren, "startsWith", "prefix", &arguments[1])?;
+    return Ok(Some(LiteralType::Boolean(string.starts_with(&prefix))));
+}
+
+pub fn ends_with(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "endsWith", "string", &arguments[0])?;
+    let suffix = check_string(paren, "endsWith", "suffix", &arguments[1])?;
+    return Ok(Some(LiteralType::Boolean(string.ends_with(&suffix))));
+}
+
+pub fn repeat(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "repeat", "string", &arguments[0])?;
+    let count = check_integer(paren, "repeat", "count", &arguments[1])?;
+    if count < 0 {
+        return Err(error(paren, "repeat: count must not be negative."));
+    }
+    match string.len().checked_mul(count as usize) {
+        Some(length) if length <= MAX_STRING_LENGTH => return string_value(string.repeat(count as usize)),
+        _ => return Err(error(paren, &format!("repeat: the result would be longer than {} bytes.", MAX_STRING_LENGTH))),
+    }
+}
+
+pub fn chars(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let string = check_string(paren, "chars", "argument", &arguments[0])?;
+    let elements = string.chars().map(|c| Some(LiteralType::String(c.to_string()))).collect();
+    return Ok(Some(LiteralType::Tuple(Rc::new(elements))));
+}
@@ -0,0 +1,396 @@
+// Date and time natives.
+//
+// Timestamps are seconds since the Unix epoch and durations are seconds, both
+// plain numbers, so `now() + duration("2h")` and `end - start` need nothing
+// special. Wall and monotonic time come from the interpreter's `Clock`, which
+// can be replaced to make scripts that look at the time reproducible.
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use crate::interpreter::Interpreter;
+use crate::native::{check_number, check_string, error, NativeResult};
+use crate::token::{LiteralType, Token};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+// The timestamps formatTime takes: the range of JavaScript's Date, some
+// 270,000 years either side of 1970, which keeps the microseconds in an i64.
+const MAX_TIMESTAMP: f64 = 8.64e12;
+const WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+pub trait Clock {
+    // Seconds since the Unix epoch.
+    fn now(&self) -> f64;
+    // Seconds since some fixed point in the past; never goes backwards.
+    fn monotonic(&self) -> f64;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self { start: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> f64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+    }
+
+    fn monotonic(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+}
+
+// Time stands still: `now()` is always the same and no time ever elapses.
+pub struct FixedClock {
+    time: f64,
+}
+
+impl FixedClock {
+    pub fn new(time: f64) -> Self {
+        Self { time }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> f64 {
+        self.time
+    }
+
+    fn monotonic(&self) -> f64 {
+        0.0
+    }
+}
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("now", 0, now);
+    interpreter.register_native("monotonic", 0, monotonic);
+    interpreter.register_native("formatTime", 3, format_time);
+    interpreter.register_native("parseTime", 1, parse_time);
+    interpreter.register_native("duration", 1, duration);
+    interpreter.register_native("formatDuration", 1, format_duration);
+}
+
+pub fn now(interpreter: &mut Interpreter, _paren: &Token, _arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    return Ok(Some(LiteralType::Number(interpreter.clock().now())));
+}
+
+pub fn monotonic(interpreter: &mut Interpreter, _paren: &Token, _arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    return Ok(Some(LiteralType::Number(interpreter.clock().monotonic())));
+}
+
+// `formatTime(timestamp, pattern, offset)` where offset is nil for UTC or a
+// string like "+05:30".
+pub fn format_time(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let timestamp = check_number(paren, "formatTime", "timestamp", &arguments[0])?;
+    let pattern = check_string(paren, "formatTime", "pattern", &arguments[1])?;
+    let offset = match &arguments[2] {
+        None => 0,
+        offset => {
+            let offset = check_string(paren, "formatTime", "offset", offset)?;
+            match parse_offset(&offset) {
+                Some(offset) => offset,
+                None => return Err(error(paren, &format!("formatTime: invalid UTC offset '{}'.", offset))),
+            }
+        }
+    };
+    let time = match DateTime::from_timestamp(timestamp, offset) {
+        Some(time) => time,
+        None => return Err(error(paren, "formatTime: timestamp must be a number of seconds within 8.64e12 of 1970.")),
+    };
+    match time.format(&pattern) {
+        Ok(text) => return Ok(Some(LiteralType::String(text))),
+        Err(directive) => return Err(error(paren, &format!("formatTime: unknown directive '%{}'.", directive))),
+    }
+}
+
+// Accepts `2024-05-01`, `2024-05-01T13:45`, `2024-05-01 13:45:30.25Z`,
+// `2024-05-01T13:45:30+02:00` and so on. Without an offset the time is UTC.
+pub fn parse_time(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let text = check_string(paren, "parseTime", "argument", &arguments[0])?;
+    match parse_iso8601(&text) {
+        Some(timestamp) => return Ok(Some(LiteralType::Number(timestamp))),
+        None => return Err(error(paren, &format!("parseTime: '{}' is not an ISO-8601 date or timestamp.", text))),
+    }
+}
+
+// `duration("1h30m")` is 5400. Units are d, h, m, s and ms.
+pub fn duration(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let text = check_string(paren, "duration", "argument", &arguments[0])?;
+    match parse_duration(&text) {
+        Some(seconds) => return Ok(Some(LiteralType::Number(seconds))),
+        None => return Err(error(paren, &format!("duration: invalid duration '{}'.", text))),
+    }
+}
+
+pub fn format_duration(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let seconds = check_number(paren, "formatDuration", "argument", &arguments[0])?;
+    if !seconds.is_finite() {
+        return Err(error(paren, "formatDuration: argument must be finite."));
+    }
+
+    let mut text = String::new();
+    if seconds < 0.0 {
+        text.push('-');
+    }
+    let mut rest = seconds.abs();
+    for (unit, size) in [("d", 86400.0), ("h", 3600.0), ("m", 60.0)] {
+        let count = (rest / size).floor();
+        if count > 0.0 {
+            text.push_str(&format!("{}{}", count, unit));
+            rest -= count * size;
+        }
+    }
+    if rest > 0.0 || text.is_empty() || text == "-" {
+        // Rounded to microseconds to hide binary floating point noise.
+        text.push_str(&format!("{}s", (rest * 1e6).round() / 1e6));
+    }
+    return Ok(Some(LiteralType::String(text)));
+}
+
+// A calendar date and time of day at some UTC offset.
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    microsecond: u32,
+    weekday: usize,
+    day_of_year: u32,
+    offset: i64,
+}
+
+impl DateTime {
+    // None if the timestamp is out of range, NaN included.
+    fn from_timestamp(timestamp: f64, offset: i64) -> Option<Self> {
+        if timestamp.is_nan() || timestamp.abs() > MAX_TIMESTAMP {
+            return None;
+        }
+        let micros_total = ((timestamp * 1e6).round() as i64).checked_add(offset.checked_mul(1_000_000)?)?;
+        let seconds = micros_total.div_euclid(1_000_000);
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        let second_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        Some(Self {
+            year,
+            month,
+            day,
+            hour: (second_of_day / 3600) as u32,
+            minute: (second_of_day / 60 % 60) as u32,
+            second: (second_of_day % 60) as u32,
+            microsecond: micros_total.rem_euclid(1_000_000) as u32,
+            weekday: (days + 4).rem_euclid(7) as usize,
+            day_of_year: (days - days_from_civil(year, 1, 1) + 1) as u32,
+            offset,
+        })
+    }
+
+    // strftime-style. An unknown directive is returned as the error.
+    fn format(&self, pattern: &str) -> Result<String, char> {
+        let mut text = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+
+            let directive = chars.next().unwrap_or('%');
+            match directive {
+                'Y' => text.push_str(&format!("{:04}", self.year)),
+                'y' => text.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                'm' => text.push_str(&format!("{:02}", self.month)),
+                'd' => text.push_str(&format!("{:02}", self.day)),
+                'e' => text.push_str(&format!("{:2}", self.day)),
+                'j' => text.push_str(&format!("{:03}", self.day_of_year)),
+                'H' => text.push_str(&format!("{:02}", self.hour)),
+                'I' => text.push_str(&format!("{:02}", (self.hour + 11) % 12 + 1)),
+                'p' => text.push_str(if self.hour < 12 { "AM" } else { "PM" }),
+                'M' => text.push_str(&format!("{:02}", self.minute)),
+                'S' => text.push_str(&format!("{:02}", self.second)),
+                'f' => text.push_str(&format!("{:06}", self.microsecond)),
+                'a' => text.push_str(&WEEKDAYS[self.weekday][..3]),
+                'A' => text.push_str(WEEKDAYS[self.weekday]),
+                'b' => text.push_str(&MONTHS[self.month as usize - 1][..3]),
+                'B' => text.push_str(MONTHS[self.month as usize - 1]),
+                'z' => {
+                    let sign = if self.offset < 0 { '-' } else { '+' };
+                    let offset = self.offset.abs();
+                    text.push_str(&format!("{}{:02}{:02}", sign, offset / 3600, offset / 60 % 60));
+                }
+                'F' => text.push_str(&format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)),
+                'T' => text.push_str(&format!("{:02}:{:02}:{:02}", self.hour, self.minute, self.second)),
+                '%' => text.push('%'),
+                other => return Err(other),
+            }
+        }
+        return Ok(text);
+    }
+}
+
+// Days since 1970-01-01 of a date in the proleptic Gregorian calendar, and
+// back. From Howard Hinnant's "chrono-Compatible Low-Level Date Algorithms".
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    return era * 146097 + day_of_era - 719468;
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    return (year, month, day);
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// "Z", "+05:30", "-0800" or "+02", in seconds east of UTC.
+fn parse_offset(text: &str) -> Option<i64> {
+    if text == "Z" {
+        return Some(0);
+    }
+
+    let sign = match text.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    // Exactly two digits each, so "+0:30" or "+5" aren't misread.
+    let (hours, minutes) = match text.len() {
+        3 => (text.get(1..3)?, "00"),
+        5 => (text.get(1..3)?, text.get(3..5)?),
+        6 if text.as_bytes()[3] == b':' => (text.get(1..3)?, text.get(4..6)?),
+        _ => return None,
+    };
+    if !hours.bytes().chain(minutes.bytes()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i64 = hours.parse().ok()?;
+    let minutes: i64 = minutes.parse().ok()?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    return Some(sign * (hours * 3600 + minutes * 60));
+}
+
+pub fn parse_iso8601(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if !text.is_ascii() || text.len() < 10 {
+        return None;
+    }
+
+    let number = |range: std::ops::Range<usize>| -> Option<u32> {
+        let digits = text.get(range)?;
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        return digits.parse().ok();
+    };
+
+    let year = number(0..4)? as i64;
+    let month = number(5..7)?;
+    let day = number(8..10)?;
+    if &text[4..5] != "-" || &text[7..8] != "-" || !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    let mut seconds = (days_from_civil(year, month, day) * SECONDS_PER_DAY) as f64;
+    let rest = &text[10..];
+    if rest.is_empty() {
+        return Some(seconds);
+    }
+
+    // The time of day: `THH:MM`, then optionally `:SS` and a fraction.
+    if !rest.starts_with('T') && !rest.starts_with(' ') {
+        return None;
+    }
+    let hour = number(11..13)?;
+    let minute = number(14..16)?;
+    if text.get(13..14)? != ":" || hour > 23 || minute > 59 {
+        return None;
+    }
+    seconds += (hour * 3600 + minute * 60) as f64;
+
+    let mut end = 16;
+    if text.get(16..17) == Some(":") {
+        let second = number(17..19)?;
+        if second > 59 {
+            return None;
+        }
+        seconds += second as f64;
+        end = 19;
+
+        if text.get(19..20) == Some(".") {
+            let fraction: String = text[20..].chars().take_while(|c| c.is_ascii_digit()).collect();
+            if fraction.is_empty() {
+                return None;
+            }
+            seconds += format!("0.{}", fraction).parse::<f64>().ok()?;
+            end = 20 + fraction.len();
+        }
+    }
+
+    let offset = &text[end..];
+    if offset.is_empty() {
+        return Some(seconds);
+    }
+    return Some(seconds - parse_offset(offset)? as f64);
+}
+
+fn parse_duration(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (sign, mut rest) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text),
+    };
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut seconds = 0.0;
+    while !rest.is_empty() {
+        let number_length = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+        let count: f64 = rest[..number_length].parse().ok()?;
+        rest = &rest[number_length..];
+
+        let unit_length = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        let unit = match &rest[..unit_length] {
+            "d" => 86400.0,
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        rest = &rest[unit_length..];
+        seconds += count * unit;
+    }
+    return Some(sign * seconds);
+}
@@ -1,23 +1,34 @@
 use std::fmt::{self, Display};
+use std::rc::Rc;
+use bytes::Bytes;
+use crate::byte_string;
+use crate::fiber::Channel;
+use crate::lox_enum::{LoxEnum, LoxEnumVariant};
+use crate::lox_range::LoxRange;
+use crate::lox_iterator::LoxIterator;
+use crate::lox_map::LoxMap;
+use crate::lox_module::LoxModule;
 use crate::lox_callable::LoxCallables;
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum TokenType {
     // Single-character tokens
-    LeftParen, RightParen, LeftBrace, RightBrace,
-    Comma, Dot, Minus, Plus, Star, Semicolon, Slash,
+    LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
+    Comma, Colon, Dot, Minus, Plus, Star, Semicolon, Slash,
 
     // One or two character tokens
     Equal, EqualEqual,
     Bang, BangEqual,
     Less, LessEqual,
     Greater, GreaterEqual,
+    QuestionQuestion, QuestionDot,
+    DotDot, DotDotEqual, Pipe,
 
     // Literals
-    String, Number, Identifier,
+    String, Bytes, Number, Identifier,
 
     // Keywords
-    And, Class, Else, False, Fun, For, If, Nil, Or,
+    And, Assert, Break, Class, Continue, Defer, Else, Enum, False, Fun, For, If, In, Nil, Or,
     Print, Return, Super, This, True, Var, While,
 
     // End of file
@@ -31,7 +42,10 @@ impl TokenType {
             TokenType::RightParen => "RIGHT_PAREN",
             TokenType::LeftBrace => "LEFT_BRACE",
             TokenType::RightBrace => "RIGHT_BRACE",
+            TokenType::LeftBracket => "LEFT_BRACKET",
+            TokenType::RightBracket => "RIGHT_BRACKET",
             TokenType::Comma => "COMMA",
+            TokenType::Colon => "COLON",
             TokenType::Dot => "DOT",
             TokenType::Minus => "MINUS",
             TokenType::Plus => "PLUS",
@@ -46,16 +60,28 @@ impl TokenType {
             TokenType::LessEqual => "LESS_EQUAL",
             TokenType::Greater => "GREATER",
             TokenType::GreaterEqual => "GREATER_EQUAL",
+            TokenType::QuestionQuestion => "QUESTION_QUESTION",
+            TokenType::QuestionDot => "QUESTION_DOT",
+            TokenType::DotDot => "DOT_DOT",
+            TokenType::DotDotEqual => "DOT_DOT_EQUAL",
+            TokenType::Pipe => "PIPE",
             TokenType::String => "STRING",
+            TokenType::Bytes => "BYTES",
             TokenType::Number => "NUMBER",
             TokenType::Identifier => "IDENTIFIER",
             TokenType::And => "AND",
+            TokenType::Assert => "ASSERT",
+            TokenType::Break => "BREAK",
             TokenType::Class => "CLASS",
+            TokenType::Continue => "CONTINUE",
+            TokenType::Defer => "DEFER",
             TokenType::Else => "ELSE",
+            TokenType::Enum => "ENUM",
             TokenType::False => "FALSE",
             TokenType::Fun => "FUN",
             TokenType::For => "FOR",
             TokenType::If => "IF",
+            TokenType::In => "IN",
             TokenType::Nil => "NIL",
             TokenType::Or => "OR",
             TokenType::Print => "PRINT",
@@ -79,22 +105,79 @@ impl Display for TokenType {
 #[derive(Clone, PartialEq, Debug)]
 pub enum LiteralType {
     String(String),
+    Bytes(Bytes),
     Number(f64),
     Boolean(bool),
     LoxCallable(LoxCallables),
+    Channel(Channel),
+    Enum(Rc<LoxEnum>),
+    EnumVariant(LoxEnumVariant),
+    Tuple(Rc<Vec<Option<LiteralType>>>),
+    Range(LoxRange),
+    Iterator(LoxIterator),
+    Map(LoxMap),
+    Module(LoxModule),
 }
 
 impl Display for LiteralType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
         match self {
             LiteralType::String(s) => write!(f, "{}", s),
+            LiteralType::Bytes(b) => write!(f, "{}", byte_string::repr(b)),
             LiteralType::Number(n) => write!(f, "{:?}", n),
             LiteralType::Boolean(b) => write!(f, "{}", b),
             LiteralType::LoxCallable(c) => write!(f, "{}", c),
+            LiteralType::Channel(c) => write!(f, "{}", c),
+            LiteralType::Enum(e) => write!(f, "{}", e),
+            LiteralType::EnumVariant(v) => write!(f, "{}", v),
+            LiteralType::Range(r) => write!(f, "{}", r),
+            LiteralType::Iterator(i) => write!(f, "{}", i),
+            LiteralType::Map(m) => write!(f, "{}", m),
+            LiteralType::Module(m) => write!(f, "{}", m),
+            LiteralType::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| format_element(e)).collect();
+                if elements.len() == 1 {
+                    write!(f, "({},)", elements[0])
+                } else {
+                    write!(f, "({})", elements.join(", "))
+                }
+            }
         }
     }
 }
 
+impl LiteralType {
+    // The name `type()` reports. Nil has no LiteralType, it's "nil".
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LiteralType::String(_) => "string",
+            LiteralType::Bytes(_) => "bytes",
+            LiteralType::Number(_) => "number",
+            LiteralType::Boolean(_) => "bool",
+            LiteralType::LoxCallable(_) => "function",
+            LiteralType::Channel(_) => "channel",
+            LiteralType::Enum(_) => "enum",
+            LiteralType::EnumVariant(_) => "variant",
+            LiteralType::Tuple(_) => "tuple",
+            LiteralType::Range(_) => "range",
+            LiteralType::Iterator(_) => "iterator",
+            LiteralType::Map(_) => "map",
+            LiteralType::Module(_) => "module",
+        }
+    }
+}
+
+// How a value is shown inside a collection: like `print` would show it, except
+// that strings are quoted.
+pub fn format_element(value: &Option<LiteralType>) -> String {
+    match value {
+        Some(LiteralType::String(s)) => format!("{:?}", s),
+        Some(LiteralType::Number(n)) => n.to_string(),
+        Some(literal) => literal.to_string(),
+        None => "nil".to_owned(),
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct Token {
     pub token_type: TokenType,
@@ -135,14 +218,34 @@ impl Token {
     }
 }
 
+impl Token {
+    pub fn new_bytes(token_type: TokenType, lexeme: String, literal: Bytes, line: usize) -> Self {
+        Self {
+            token_type,
+            lexeme,
+            literal: Some(LiteralType::Bytes(literal)),
+            line,
+        }
+    }
+}
+
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} {} {}", self.token_type, self.lexeme, 
             match &self.literal {
                 Some(LiteralType::String(literal)) => format!("{}", literal),
+                Some(LiteralType::Bytes(literal)) => byte_string::repr(literal),
                 Some(LiteralType::Number(literal)) => format!("{:?}", literal),
                 Some(LiteralType::Boolean(literal)) => format!("{}", literal),
                 Some(LiteralType::LoxCallable(literal)) => format!("{}", literal),
+                Some(LiteralType::Channel(literal)) => format!("{}", literal),
+                Some(LiteralType::Enum(literal)) => format!("{}", literal),
+                Some(LiteralType::EnumVariant(literal)) => format!("{}", literal),
+                Some(LiteralType::Tuple(literal)) => format!("{}", LiteralType::Tuple(Rc::clone(literal))),
+                Some(LiteralType::Range(literal)) => format!("{}", literal),
+                Some(LiteralType::Iterator(literal)) => format!("{}", literal),
+                Some(LiteralType::Map(literal)) => format!("{}", literal),
+                Some(LiteralType::Module(literal)) => format!("{}", literal),
                 None => "null".to_owned(),
             }
         )
@@ -0,0 +1,46 @@
+// Asking for a value's type and converting between types.
+use crate::interpreter::Interpreter;
+use crate::native::{error, NativeResult};
+use crate::token::{LiteralType, Token};
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("type", 1, type_of);
+    interpreter.register_native("str", 1, str);
+    interpreter.register_native("num", 1, num);
+    interpreter.register_native("bool", 1, bool);
+}
+
+pub fn type_of(_interpreter: &mut Interpreter, _paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    let name = match &arguments[0] {
+        Some(value) => value.type_name(),
+        None => "nil",
+    };
+    return Ok(Some(LiteralType::String(name.to_owned())));
+}
+
+// The text `print` would show.
+pub fn str(interpreter: &mut Interpreter, _paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    return Ok(Some(LiteralType::String(interpreter.stringify(&arguments[0]))));
+}
+
+// Parses a decimal number, allowing surrounding whitespace, a sign and an
+// exponent. Anything else, including "inf" and "NaN", gives nil.
+pub fn num(_interpreter: &mut Interpreter, paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    match &arguments[0] {
+        Some(LiteralType::Number(n)) => return Ok(Some(LiteralType::Number(*n))),
+        Some(LiteralType::String(s)) => {
+            let s = s.trim();
+            let is_decimal = s.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'));
+            match s.parse::<f64>() {
+                Ok(n) if is_decimal => return Ok(Some(LiteralType::Number(n))),
+                _ => return Ok(None),
+            }
+        }
+        _ => return Err(error(paren, "num: argument must be a string or number.")),
+    }
+}
+
+// Lox truthiness: only nil and false are false.
+pub fn bool(interpreter: &mut Interpreter, _paren: &Token, arguments: Vec<Option<LiteralType>>) -> NativeResult {
+    return Ok(Some(LiteralType::Boolean(interpreter.is_truthy(&arguments[0]))));
+}
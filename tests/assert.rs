@@ -0,0 +1,67 @@
+mod common;
+
+use common::{output, run, runtime_error};
+
+#[test]
+fn passing_assertions_do_nothing() {
+    let source = "
+        assert true;
+        assert 1 + 1 == 2;
+        assert 3 in 1..5, \"in range\";
+        print \"done\";
+    ";
+    assert_eq!(output(source), "done\n");
+}
+
+#[test]
+fn failures_quote_the_condition_and_its_operands() {
+    let source = "
+        var x = 3;
+        assert x  +  1 == 5;
+    ";
+    assert_eq!(runtime_error(source), "Assertion failed: x + 1 == 5 (left: 4, right: 5)");
+    assert_eq!(runtime_error("assert \"a\" != \"a\";"), "Assertion failed: \"a\" != \"a\" (left: \"a\", right: \"a\")");
+    assert_eq!(runtime_error("assert (1, 2) == (1, 3);"), "Assertion failed: (1, 2) == (1, 3) (left: (1, 2), right: (1, 3))");
+}
+
+#[test]
+fn string_operands_are_quoted_so_they_differ_from_numbers() {
+    assert_eq!(runtime_error("assert \"1\" == 1;"), "Assertion failed: \"1\" == 1 (left: \"1\", right: 1)");
+    assert_eq!(runtime_error("assert nil == \"nil\";"), "Assertion failed: nil == \"nil\" (left: nil, right: \"nil\")");
+    assert_eq!(runtime_error("assert 1.5 != 1.5;"), "Assertion failed: 1.5 != 1.5 (left: 1.5, right: 1.5)");
+}
+
+#[test]
+fn failures_of_other_conditions_quote_only_the_condition() {
+    assert_eq!(runtime_error("assert nil;"), "Assertion failed: nil");
+    assert_eq!(runtime_error("assert !true and false;"), "Assertion failed: !true and false");
+}
+
+#[test]
+fn a_custom_message_is_appended() {
+    assert_eq!(
+        runtime_error("assert 1 > 2, \"custom \" + \"message\";"),
+        "Assertion failed: 1 > 2 (left: 1, right: 2): custom message"
+    );
+    assert_eq!(runtime_error("assert false, 42;"), "Assertion failed: false: 42");
+}
+
+#[test]
+fn failures_report_the_assert_line() {
+    let run = run("print 1;\n\nassert 1 == 2;");
+    assert_eq!(run.stdout, "1\n");
+    assert_eq!(run.stderr, "Assertion failed: 1 == 2 (left: 1, right: 2)\n[line 3]\n");
+    assert_eq!(run.status, 70);
+}
+
+#[test]
+fn errors_evaluating_the_condition_are_reported_as_they_are() {
+    assert_eq!(runtime_error("assert 1 < \"a\";"), "Operand must be a number.");
+}
+
+#[test]
+fn a_missing_semicolon_is_a_parse_error() {
+    let run = run("assert true");
+    assert_eq!(run.status, 65);
+    assert_eq!(run.stderr, "[line 1] Error at end: Expect ';' after assertion.\n");
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{output, run, runtime_error};
+
+#[test]
+fn byte_string_literals_understand_escapes() {
+    let source = r#"
+        var request = b"GET \x00\n\xff";
+        print request;
+        print b"a\"b\\c\t\r\0";
+        print len(request);
+        print type(request);
+    "#;
+    assert_eq!(output(source), "b\"GET \\x00\\n\\xff\"\nb\"a\\\"b\\\\c\\t\\r\\x00\"\n7\nbytes\n");
+}
+
+#[test]
+fn byte_strings_index_concatenate_and_compare() {
+    let source = r#"
+        var b = b"GET";
+        print b[0];
+        print b + b"!";
+        print b == b"GET";
+        print b == "GET";
+        print slice(b, 1, 3);
+    "#;
+    assert_eq!(output(source), "71\nb\"GET!\"\ntrue\nfalse\nb\"ET\"\n");
+}
+
+#[test]
+fn bytes_convert_to_and_from_text() {
+    let source = r#"
+        print hex(b"\x01\xab");
+        print fromHex("01AB");
+        print base64(b"hello");
+        print fromBase64("aGVsbG8=");
+        print encodeUtf8("é");
+        print decodeUtf8(b"\xc3\xa9");
+    "#;
+    assert_eq!(output(source), "01ab\nb\"\\x01\\xab\"\naGVsbG8=\nb\"hello\"\nb\"\\xc3\\xa9\"\né\n");
+}
+
+#[test]
+fn bad_escapes_are_scan_errors() {
+    for source in [r#"print b"\xZZ";"#, r#"print b"\x4";"#, r#"print b"\q";"#] {
+        let run = run(source);
+        assert_eq!(run.status, 65, "{}", source);
+        assert_eq!(run.stderr, "[line 1] Error: Invalid escape sequence in byte string.\n");
+    }
+}
+
+#[test]
+fn bad_conversions_are_runtime_errors() {
+    assert_eq!(runtime_error(r#"decodeUtf8(b"\xff");"#), "decodeUtf8: invalid utf-8 sequence of 1 bytes from index 0.");
+    assert_eq!(runtime_error(r#"fromHex("abc");"#), "fromHex: argument is not an even number of hex digits.");
+    assert_eq!(runtime_error(r#"fromHex("zz");"#), "fromHex: 'z' is not a hex digit.");
+    assert_eq!(runtime_error(r#"fromHex("0aé1");"#), "fromHex: 'é' is not a hex digit.");
+    assert_eq!(runtime_error(r#"fromHex(1);"#), "fromHex: argument must be a string.");
+    assert_eq!(runtime_error(r#"fromBase64("!!");"#), "fromBase64: argument is not valid base64.");
+    assert_eq!(runtime_error(r#"hex("x");"#), "hex: argument must be bytes.");
+}
+
+#[test]
+fn out_of_range_positions_are_runtime_errors() {
+    assert_eq!(runtime_error(r#"b"abc"[3];"#), "Index 3 out of range for length 3.");
+    assert_eq!(runtime_error(r#"slice(b"abc", 2, 1);"#), "slice: start must not be after end.");
+    assert_eq!(runtime_error(r#"slice(b"abc", 0, 4);"#), "slice: index must be an integer between 0 and 3.");
+}
@@ -0,0 +1,83 @@
+// Runs Lox scripts through the interpreter binary for the integration tests.
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+pub struct Run {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: i32,
+}
+
+// `flags` go before the command and `args` after the script's path.
+pub fn run_with(flags: &[&str], source: &str, args: &[&str]) -> Run {
+    execute("run", flags, source, args, None)
+}
+
+// Runs the script with `input` as its standard input.
+pub fn run_with_input(source: &str, input: &[u8]) -> Run {
+    execute("run", &[], source, &[], Some(input))
+}
+
+// Runs another of the binary's commands, like `parse` or `evaluate`, on the source.
+pub fn run_command(command: &str, source: &str) -> Run {
+    execute(command, &[], source, &[], None)
+}
+
+fn execute(command_name: &str, flags: &[&str], source: &str, args: &[&str], input: Option<&[u8]>) -> Run {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let path: PathBuf = std::env::temp_dir().join(format!(
+        "lox-test-{}-{}.lox",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&path, source).unwrap();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_codecrafters-interpreter"));
+    command.args(flags).arg(command_name).arg(&path).args(args);
+    let output = match input {
+        None => command.output().unwrap(),
+        Some(input) => {
+            let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+            let mut stdin = child.stdin.take().unwrap();
+            let input = input.to_vec();
+            // Written from another thread so a script that stops reading early can't block us.
+            let writer = thread::spawn(move || {
+                let _ = stdin.write_all(&input);
+            });
+            let output = child.wait_with_output().unwrap();
+            writer.join().unwrap();
+            output
+        }
+    };
+    fs::remove_file(&path).unwrap();
+
+    Run {
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+        status: output.status.code().unwrap(),
+    }
+}
+
+pub fn run(source: &str) -> Run {
+    run_with(&[], source, &[])
+}
+
+// The script's output, failing the test if it didn't run cleanly.
+pub fn output(source: &str) -> String {
+    let run = run(source);
+    assert_eq!(run.status, 0, "script failed: {}", run.stderr);
+    run.stdout
+}
+
+// The runtime error a script stops with.
+pub fn runtime_error(source: &str) -> String {
+    let run = run(source);
+    assert_eq!(run.status, 70, "expected a runtime error, got output: {}", run.stdout);
+    run.stderr.lines().next().unwrap_or("").to_owned()
+}
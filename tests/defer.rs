@@ -0,0 +1,99 @@
+mod common;
+
+use common::{output, run};
+
+#[test]
+fn deferred_statements_run_last_first_at_block_exit() {
+    let source = "
+        {
+            defer print 1;
+            defer print 2;
+            print 0;
+        }
+        print 3;
+    ";
+    assert_eq!(output(source), "0\n2\n1\n3\n");
+}
+
+#[test]
+fn deferred_statements_run_when_returning() {
+    let source = "
+        fun f() {
+            defer print \"deferred\";
+            return \"returned\";
+        }
+        print f();
+    ";
+    assert_eq!(output(source), "deferred\nreturned\n");
+}
+
+#[test]
+fn deferred_statements_run_when_unwinding_a_runtime_error() {
+    let source = "
+        fun f() {
+            defer print \"cleanup\";
+            print nil + 1;
+        }
+        f();
+    ";
+    let run = run(source);
+    assert_eq!(run.stdout, "cleanup\n");
+    assert_eq!(run.status, 70);
+}
+
+#[test]
+fn a_returned_call_runs_before_the_callers_deferred_statements() {
+    let source = "
+        fun h() { print \"h body\"; return 1; }
+        fun g() {
+            defer print \"g defer\";
+            return h();
+        }
+        print g();
+    ";
+    assert_eq!(output(source), "h body\ng defer\n1\n");
+}
+
+#[test]
+fn a_resource_is_still_open_while_the_returned_call_uses_it() {
+    let source = "
+        var open = false;
+        fun close() { open = false; print \"closed\"; }
+        fun read() { return open; }
+        fun withFile() {
+            open = true;
+            defer close();
+            {
+                return read();
+            }
+        }
+        print withFile();
+    ";
+    assert_eq!(output(source), "closed\ntrue\n");
+}
+
+#[test]
+fn a_returned_call_at_the_top_level_runs_before_its_deferred_statements() {
+    let source = "
+        fun h() { print \"h body\"; }
+        defer print \"top defer\";
+        return h();
+    ";
+    assert_eq!(output(source), "h body\ntop defer\n");
+}
+
+#[test]
+fn deferred_statements_in_a_caller_do_not_stop_its_callees_tail_calls() {
+    let source = "
+        fun count(n) {
+            if (n == 0) return \"done\";
+            return count(n - 1);
+        }
+        fun main() {
+            defer print \"main defer\";
+            print count(100000);
+        }
+        main();
+    ";
+    assert_eq!(output(source), "done\nmain defer\n");
+}
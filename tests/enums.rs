@@ -0,0 +1,64 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn variants_print_and_compare() {
+    let source = "
+        enum Color { Red, Green, Blue }
+        print Color.Red;
+        print Color.Red == Color.Red;
+        print Color.Red == Color.Green;
+        var c = Color.Blue;
+        print c == Color.Blue;
+    ";
+    assert_eq!(output(source), "Color.Red\ntrue\nfalse\ntrue\n");
+}
+
+#[test]
+fn iterating_an_enum_yields_every_variant_in_order() {
+    let source = "
+        enum Color { Red, Green, Blue }
+        for (var c in Color) print c;
+    ";
+    assert_eq!(output(source), "Color.Red\nColor.Green\nColor.Blue\n");
+}
+
+#[test]
+fn separately_declared_enums_are_distinct() {
+    let source = "
+        enum Color { Red, Green }
+        var first = Color;
+        var firstRed = Color.Red;
+        {
+            enum Color { Red, Green }
+            print first == Color;
+            print firstRed == Color.Red;
+            print firstRed in Color;
+            print Color.Red in Color;
+        }
+        print first == Color;
+    ";
+    assert_eq!(output(source), "false\nfalse\nfalse\ntrue\ntrue\n");
+}
+
+#[test]
+fn each_evaluation_of_a_declaration_makes_a_new_enum() {
+    let source = "
+        fun make() {
+            enum Status { Ok, Failed }
+            return Status.Ok;
+        }
+        print make() == make();
+    ";
+    assert_eq!(output(source), "false\n");
+}
+
+#[test]
+fn unknown_variants_are_runtime_errors() {
+    let source = "
+        enum Color { Red }
+        print Color.Purple;
+    ";
+    assert_eq!(runtime_error(source), "Undefined variant 'Purple' on enum Color.");
+}
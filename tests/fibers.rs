@@ -0,0 +1,180 @@
+mod common;
+
+use common::{output, run, runtime_error};
+
+#[test]
+fn fibers_run_round_robin_after_the_script_yields() {
+    let source = "
+        fun worker(name) {
+            fun run() {
+                for (var i = 0; i < 3; i = i + 1) {
+                    print name + str(i);
+                    yield();
+                }
+            }
+            return run;
+        }
+        spawn(worker(\"a\"));
+        spawn(worker(\"b\"));
+        print \"main\";
+        yield();
+        print \"main again\";
+    ";
+    assert_eq!(output(source), "main\na0\nb0\nmain again\na1\nb1\na2\nb2\n");
+}
+
+#[test]
+fn channels_hand_values_over_in_order() {
+    let source = "
+        var numbers = channel();
+        fun produce() {
+            for (var i = 1; i <= 3; i = i + 1) {
+                print \"send \" + str(i);
+                send(numbers, i);
+            }
+            close(numbers);
+        }
+        fun consume() {
+            var value = receive(numbers);
+            while (value != nil) {
+                print \"got \" + str(value);
+                value = receive(numbers);
+            }
+            print \"done\";
+        }
+        spawn(consume);
+        spawn(produce);
+    ";
+    assert_eq!(output(source), "send 1\nsend 2\nsend 3\ngot 1\ngot 2\ngot 3\ndone\n");
+}
+
+#[test]
+fn the_script_can_receive_from_fibers() {
+    let source = "
+        var results = channel();
+        fun square(n) {
+            fun run() { send(results, n * n); }
+            return run;
+        }
+        spawn(square(3));
+        spawn(square(4));
+        print receive(results) + receive(results);
+    ";
+    assert_eq!(output(source), "25\n");
+}
+
+#[test]
+fn closed_channels_give_nil_once_empty() {
+    let source = "
+        var c = channel();
+        send(c, 1);
+        close(c);
+        print receive(c);
+        print receive(c);
+        print receive(c);
+    ";
+    assert_eq!(output(source), "1\nnil\nnil\n");
+
+    let source = "
+        var c = channel();
+        close(c);
+        send(c, 1);
+    ";
+    assert_eq!(runtime_error(source), "send: channel is closed.");
+
+    let source = "
+        var c = channel();
+        close(c);
+        close(c);
+    ";
+    assert_eq!(runtime_error(source), "close: channel is already closed.");
+}
+
+#[test]
+fn receiving_when_nothing_can_send_is_a_deadlock() {
+    let message = "receive: every fiber is blocked, nothing can send to this channel.";
+    assert_eq!(runtime_error("receive(channel());"), message);
+
+    let source = "
+        var a = channel();
+        var b = channel();
+        fun first() { receive(a); send(b, 1); }
+        fun second() { receive(b); send(a, 1); }
+        spawn(first);
+        spawn(second);
+        receive(a);
+    ";
+    assert_eq!(runtime_error(source), message);
+}
+
+#[test]
+fn errors_in_fibers_stop_the_script() {
+    let source = "
+        fun fail() { print nil + 1; }
+        spawn(fail);
+        yield();
+        print \"unreachable\";
+    ";
+    assert_eq!(runtime_error(source), "Operands must be two numbers or two strings.");
+    assert_eq!(runtime_error("spawn(1);"), "spawn: argument must be a function that takes no arguments.");
+    assert_eq!(runtime_error("send(1, 2);"), "send: argument must be a channel.");
+}
+
+#[test]
+fn runaway_recursion_is_a_runtime_error() {
+    let recurse = "fun depth(n) { if (n == 0) return 0; return 1 + depth(n - 1); }";
+    assert_eq!(runtime_error(&format!("{} depth(1000000);", recurse)), "Stack overflow.");
+
+    let source = format!("
+        {}
+        fun run() {{
+            print depth(500);
+            depth(1000000);
+        }}
+        spawn(run);
+    ", recurse);
+    assert_eq!(runtime_error(&source), "Stack overflow.");
+}
+
+#[test]
+fn fibers_still_waiting_when_the_script_ends_are_reported() {
+    let source = "
+        var c = channel();
+        fun wait() {
+            defer print \"cleaned up\";
+            print \"waiting\";
+            receive(c);
+            print \"unreachable\";
+        }
+        spawn(wait);
+        print \"main done\";
+    ";
+    let run = run(source);
+    assert_eq!(run.status, 70);
+    assert_eq!(run.stdout, "main done\nwaiting\ncleaned up\n");
+    assert_eq!(run.stderr, "receive: every fiber is blocked, nothing can send to this channel.\n[line 6]\n");
+}
+
+#[test]
+fn an_error_in_one_fiber_unwinds_the_others() {
+    let source = "
+        var c = channel();
+        fun wait() {
+            defer print \"waiter cleaned up\";
+            receive(c);
+        }
+        fun spin() {
+            defer print \"spinner cleaned up\";
+            while (true) yield();
+        }
+        fun fail() { print nil + 1; }
+        spawn(wait);
+        spawn(spin);
+        spawn(fail);
+        spawn(spin);
+    ";
+    let run = run(source);
+    assert_eq!(run.status, 70);
+    assert_eq!(run.stdout, "waiter cleaned up\nspinner cleaned up\n");
+    assert_eq!(run.stderr, "Operands must be two numbers or two strings.\n[line 11]\n");
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use common::{output, runtime_error};
+
+// An empty directory of the test's own, removed again when it ends.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("lox-fs-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn path(&self, name: &str) -> String {
+        self.0.join(name).to_str().unwrap().to_owned()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn files_are_written_appended_and_read_back() {
+    let dir = TempDir::new("write");
+    let file = dir.path("notes.txt");
+    let source = format!(r#"
+        var path = "{}";
+        print exists(path);
+        writeFile(path, b"one\n");
+        appendFile(path, b"two\n");
+        appendFile(path, "three");
+        print exists(path);
+        print readFile(path);
+        print readLines(path);
+        print fileSize(path);
+    "#, file);
+    assert_eq!(output(&source), "false\ntrue\none\ntwo\nthree\n(\"one\", \"two\", \"three\")\n13\n");
+    assert_eq!(fs::read_to_string(&file).unwrap(), "one\ntwo\nthree");
+}
+
+#[test]
+fn directories_list_sorted_and_entries_can_be_removed() {
+    let dir = TempDir::new("list");
+    fs::write(dir.path("b"), "").unwrap();
+    fs::write(dir.path("a"), "").unwrap();
+    fs::create_dir(dir.path("c")).unwrap();
+    let source = format!(r#"
+        var dir = "{}";
+        print listDir(dir);
+        remove(dir + "/a");
+        remove(dir + "/c");
+        print listDir(dir);
+    "#, dir.path(""));
+    assert_eq!(output(&source), "(\"a\", \"b\", \"c\")\n(\"b\",)\n");
+}
+
+#[test]
+fn failures_report_the_path_and_the_os_error() {
+    let dir = TempDir::new("errors");
+    let missing = dir.path("missing");
+    let not_found = "No such file or directory (os error 2)";
+
+    assert_eq!(runtime_error(&format!(r#"readFile("{}");"#, missing)), format!("readFile: {}: {}.", missing, not_found));
+    assert_eq!(runtime_error(&format!(r#"readLines("{}");"#, missing)), format!("readLines: {}: {}.", missing, not_found));
+    assert_eq!(runtime_error(&format!(r#"fileSize("{}");"#, missing)), format!("fileSize: {}: {}.", missing, not_found));
+    assert_eq!(runtime_error(&format!(r#"listDir("{}");"#, missing)), format!("listDir: {}: {}.", missing, not_found));
+    assert_eq!(runtime_error(&format!(r#"remove("{}");"#, missing)), format!("remove: {}: {}.", missing, not_found));
+
+    let nested = dir.path("missing/file");
+    assert_eq!(
+        runtime_error(&format!(r#"writeFile("{}", "x");"#, nested)),
+        format!("writeFile: {}: {}.", nested, not_found)
+    );
+}
+
+#[test]
+fn arguments_are_type_checked() {
+    assert_eq!(runtime_error("readFile(1);"), "readFile: path must be a string.");
+    assert_eq!(runtime_error("writeFile(\"x\", 1);"), "writeFile: contents must be a string or byte string.");
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::{output, runtime_error};
+
+fn format(arguments: &str) -> String {
+    output(&format!("print format({});", arguments)).trim_end_matches('\n').to_owned()
+}
+
+#[test]
+fn placeholders_take_values_in_order() {
+    assert_eq!(format(r#""{} + {} = {}", 1, 2.5, "three""#), "1 + 2.5 = three");
+    assert_eq!(format(r#""{{{}}} }}", nil"#), "{nil} }");
+    assert_eq!(format(r#""no placeholders""#), "no placeholders");
+}
+
+#[test]
+fn fill_align_and_width() {
+    assert_eq!(format(r#""[{:5}]", "ab""#), "[ab   ]");
+    assert_eq!(format(r#""[{:5}]", 42"#), "[   42]");
+    assert_eq!(format(r#""[{:<5}]", 42"#), "[42   ]");
+    assert_eq!(format(r#""[{:^6}]", "ab""#), "[  ab  ]");
+    assert_eq!(format(r#""[{:*>5}]", "ab""#), "[***ab]");
+    assert_eq!(format(r#""[{:-^7}]", "ab""#), "[--ab---]");
+    assert_eq!(format(r#""[{:1}]", "long""#), "[long]");
+}
+
+#[test]
+fn sign_zero_padding_and_precision() {
+    assert_eq!(format(r#""{:+} {:+}", 3, -3"#), "+3 -3");
+    assert_eq!(format(r#""{:05} {:+06.2}", -42, 3.14159"#), "-0042 +03.14");
+    assert_eq!(format(r#""{:.3}", "abcdef""#), "abc");
+    assert_eq!(format(r#""{:.0}", 2.5"#), "2");
+}
+
+#[test]
+fn integer_types() {
+    assert_eq!(format(r#""{:x} {:X} {:b} {:o}", 255, 255, 5, 8"#), "ff FF 101 10");
+    assert_eq!(format(r#""{:#x} {:#010b} {:+#o}", 255, 5, -8"#), "0xff 0b00000101 -0o10");
+    assert_eq!(
+        runtime_error(r#"format("{:x}", 1.5);"#),
+        "format: {:x} needs an integer but got 1.5."
+    );
+}
+
+#[test]
+fn width_and_precision_go_up_to_65535() {
+    let source = r#"
+        print len(format("{:65535}", ""));
+        print len(format("{:.65535}", 1));
+    "#;
+    assert_eq!(output(source), "65535\n65537\n");
+}
+
+#[test]
+fn invalid_placeholders_are_runtime_errors() {
+    let specs = [
+        "{:65536}",
+        "{:.65536}",
+        "{:99999999999999999999999}",
+        "{:.99999999999999999999999}",
+        "{:.}",
+        "{:5x5}",
+        "{:q}",
+        "{:<<<}",
+        "{name}",
+    ];
+    for spec in specs {
+        assert_eq!(
+            runtime_error(&format!(r#"format("{}", 1);"#, spec)),
+            format!("format: invalid placeholder {}.", spec)
+        );
+    }
+}
+
+#[test]
+fn unbalanced_braces_and_counts_are_runtime_errors() {
+    assert_eq!(runtime_error(r#"format("{", 1);"#), "format: unclosed '{' in pattern.");
+    assert_eq!(
+        runtime_error(r#"format("}", 1);"#),
+        "format: unmatched '}' in pattern; write '}}' for a literal brace."
+    );
+    assert_eq!(
+        runtime_error(r#"format("{} {}", 1);"#),
+        "format: pattern has 2 placeholders but 1 value was given."
+    );
+}
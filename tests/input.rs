@@ -0,0 +1,62 @@
+mod common;
+
+use common::{output, run_with_input};
+
+fn output_with_input(source: &str, input: &str) -> String {
+    let run = run_with_input(source, input.as_bytes());
+    assert_eq!(run.status, 0, "script failed: {}", run.stderr);
+    run.stdout
+}
+
+#[test]
+fn read_line_returns_lines_without_their_endings_then_nil() {
+    let source = "
+        print readLine();
+        print readLine();
+        print readLine() == \"\";
+        print readLine();
+        print readLine();
+    ";
+    assert_eq!(output_with_input(source, "first\nsecond\r\n\nlast"), "first\nsecond\ntrue\nlast\nnil\n");
+}
+
+#[test]
+fn read_all_returns_the_rest_of_the_input() {
+    let source = "
+        print readLine();
+        print readAll();
+        print readAll() == \"\";
+    ";
+    assert_eq!(output_with_input(source, "head\nbody 1\nbody 2\n"), "head\nbody 1\nbody 2\n\ntrue\n");
+}
+
+#[test]
+fn lines_are_read_lazily_by_for_in_loops() {
+    let source = "
+        var total = 0;
+        for (var line in lines()) {
+            if (line == \"stop\") break;
+            total = total + num(line);
+        }
+        print total;
+        print readLine();
+    ";
+    assert_eq!(output_with_input(source, "1\n2\n3\nstop\nrest\n"), "6\nrest\n");
+}
+
+#[test]
+fn empty_input_gives_nil_and_no_lines() {
+    let source = "
+        print readLine();
+        for (var line in lines()) print line;
+        print readAll() == \"\";
+    ";
+    assert_eq!(output(source), "nil\ntrue\n");
+}
+
+#[test]
+fn input_that_is_not_utf8_is_a_runtime_error() {
+    let run = run_with_input("readLine();", b"\xff\xfe\n");
+    assert_eq!(run.status, 70);
+    assert_eq!(run.stderr, "readLine: stream did not contain valid UTF-8.\n[line 1]\n");
+}
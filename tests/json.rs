@@ -0,0 +1,42 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn stringify_indents_each_level() {
+    let source = r#"
+        print jsonStringify(jsonParse("""{"a": [1, {"b": null}]}"""), 2);
+        print jsonStringify(jsonParse("""{"a": [1]}"""), nil);
+        print jsonStringify((1,), 0);
+    "#;
+    let expected = "{\n  \"a\": [\n    1,\n    {\n      \"b\": null\n    }\n  ]\n}\n{\"a\":[1]}\n[1]\n";
+    assert_eq!(output(source), expected);
+}
+
+#[test]
+fn stringify_rejects_indents_that_are_not_small_non_negative_integers() {
+    let message = "jsonStringify: indent must be between 0 and 10.";
+    assert_eq!(runtime_error("jsonStringify((1,), 100000000000000000000);"), message);
+    assert_eq!(runtime_error("jsonStringify((1,), 11);"), message);
+    assert_eq!(runtime_error("jsonStringify((1,), -1);"), message);
+    assert_eq!(runtime_error("jsonStringify((1,), 1.5);"), "jsonStringify: indent must be an integer.");
+}
+
+#[test]
+fn parse_decodes_surrogate_pairs() {
+    let source = concat!(
+        r#"var s = jsonParse("""["\ud83d\ude00", "\u00e9"]""");"#,
+        "print s[0] == \"\u{1F600}\";",
+        "print s[1] == \"\u{e9}\";",
+        "print len(s[0]);",
+    );
+    assert_eq!(output(source), "true\ntrue\n1\n");
+}
+
+#[test]
+fn parse_errors_say_where() {
+    assert_eq!(
+        runtime_error(r#"jsonParse("""{"a": }""");"#),
+        "jsonParse: unexpected '}' at line 1, column 7."
+    );
+}
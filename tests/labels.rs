@@ -0,0 +1,92 @@
+mod common;
+
+use common::{output, run};
+
+// The first error a script that doesn't parse is rejected with.
+fn parse_error(source: &str) -> String {
+    let run = run(source);
+    assert_eq!(run.status, 65, "expected a parse error, got output: {}", run.stdout);
+    run.stderr.lines().next().unwrap_or("").to_owned()
+}
+
+#[test]
+fn labelled_break_leaves_the_outer_loop() {
+    let source = "
+        outer: for (var i = 0; i < 3; i = i + 1) {
+            for (var j = 0; j < 3; j = j + 1) {
+                if (i == 1 and j == 1) break outer;
+                print i * 10 + j;
+            }
+        }
+        print \"after\";
+    ";
+    assert_eq!(output(source), "0\n1\n2\n10\nafter\n");
+}
+
+#[test]
+fn labelled_continue_moves_on_to_the_next_outer_iteration() {
+    let source = "
+        outer: for (var i = 0; i < 3; i = i + 1) {
+            for (var j = 0; j < 3; j = j + 1) {
+                if (j == 1) continue outer;
+                print i * 10 + j;
+            }
+        }
+        rows: for (var x in 1..3) {
+            cells: for (var y in 1..3) {
+                if (y == 2) continue rows;
+                print x + y;
+            }
+        }
+    ";
+    assert_eq!(output(source), "0\n10\n20\n2\n3\n");
+}
+
+#[test]
+fn unlabelled_break_and_continue_affect_the_innermost_loop() {
+    let source = "
+        var n = 0;
+        loop: while (true) {
+            while (true) break;
+            n = n + 1;
+            if (n < 3) continue;
+            break loop;
+        }
+        print n;
+    ";
+    assert_eq!(output(source), "3\n");
+}
+
+#[test]
+fn deferred_statements_run_when_breaking_out_of_nested_blocks() {
+    let source = "
+        outer: while (true) {
+            defer print \"outer body\";
+            while (true) {
+                defer print \"inner body\";
+                break outer;
+            }
+        }
+    ";
+    assert_eq!(output(source), "inner body\nouter body\n");
+}
+
+#[test]
+fn labels_are_checked_when_parsing() {
+    assert_eq!(parse_error("break;"), "[line 1] Error at 'break': Can't use 'break' outside of a loop.");
+    assert_eq!(parse_error("while (true) { break nope; }"), "[line 1] Error at 'nope': Undefined label 'nope'.");
+    assert_eq!(parse_error("a: while (true) { continue b; }"), "[line 1] Error at 'b': Undefined label 'b'.");
+    assert_eq!(parse_error("a: print 1;"), "[line 1] Error at 'a': Labels can only be applied to loops.");
+    assert_eq!(
+        parse_error("a: while (true) { a: while (true) { break a; } }"),
+        "[line 1] Error at 'a': Label 'a' is already used by an enclosing loop."
+    );
+}
+
+#[test]
+fn functions_cannot_break_out_of_their_callers_loops() {
+    assert_eq!(
+        parse_error("a: while (true) { fun f() { break a; } f(); }"),
+        "[line 1] Error at 'break': Can't use 'break' outside of a loop."
+    );
+}
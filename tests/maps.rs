@@ -0,0 +1,35 @@
+mod common;
+
+use common::output;
+
+#[test]
+fn maps_with_the_same_entries_are_equal_in_any_order() {
+    let source = r#"
+        var ab = jsonParse("""{"a": 1, "b": [2]}""");
+        var ba = jsonParse("""{"b": [2], "a": 1}""");
+        print ab == ba;
+        print ab == jsonParse("""{"a": 1, "b": 3}""");
+        print ab == jsonParse("""{"a": 1}""");
+        print ab == jsonParse("""{"a": 1, "c": 2}""");
+        print jsonParse("{}") == jsonParse("{}");
+    "#;
+    assert_eq!(output(source), "true\nfalse\nfalse\nfalse\ntrue\n");
+}
+
+#[test]
+fn maps_print_in_insertion_order() {
+    let source = r#"
+        print jsonParse("""{"b": 1, "a": [true, null], "b": 2}""");
+    "#;
+    assert_eq!(output(source), "{\"b\": 2, \"a\": (true, nil)}\n");
+}
+
+#[test]
+fn nested_maps_compare_by_contents() {
+    let source = r#"
+        var x = jsonParse("""{"outer": {"p": 1, "q": 2}}""");
+        var y = jsonParse("""{"outer": {"q": 2, "p": 1}}""");
+        print x == y;
+    "#;
+    assert_eq!(output(source), "true\n");
+}
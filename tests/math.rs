@@ -0,0 +1,59 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn functions_of_one_number() {
+    let source = "
+        print sqrt(16);
+        print abs(-2.5);
+        print floor(-1.5);
+        print ceil(1.2);
+        print round(2.5);
+        print sin(0);
+        print cos(0);
+        print log(E);
+        print exp(0);
+    ";
+    assert_eq!(output(source), "4\n2.5\n-2\n2\n3\n0\n1\n1\n1\n");
+}
+
+#[test]
+fn functions_of_two_numbers() {
+    let source = "
+        print pow(2, 10);
+        print min(3, -1);
+        print max(3, -1);
+        print atan2(0, 1);
+    ";
+    assert_eq!(output(source), "1024\n-1\n3\n0\n");
+}
+
+#[test]
+fn constants_and_special_values() {
+    let source = "
+        print PI;
+        print INF;
+        print sqrt(-1);
+        print isNan(NAN);
+        print isNan(1);
+        print isFinite(INF);
+        print isFinite(1);
+        print NAN == NAN;
+    ";
+    assert_eq!(output(source), "3.141592653589793\ninf\nNaN\ntrue\nfalse\nfalse\ntrue\nfalse\n");
+}
+
+#[test]
+fn arguments_must_be_numbers() {
+    assert_eq!(runtime_error("sqrt(\"4\");"), "sqrt: argument must be a number.");
+    assert_eq!(runtime_error("pow(nil, 2);"), "pow: first argument must be a number.");
+    assert_eq!(runtime_error("pow(2, nil);"), "pow: second argument must be a number.");
+    assert_eq!(runtime_error("isNan(true);"), "isNan: argument must be a number.");
+    assert_eq!(runtime_error("floor();"), "Expected 1 arguments but got 0.");
+}
+
+#[test]
+fn the_globals_are_the_math_module_members() {
+    assert_eq!(output("print sqrt == math.sqrt; print PI == math.PI;"), "true\ntrue\n");
+}
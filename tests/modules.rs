@@ -0,0 +1,78 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn natives_are_members_of_their_modules() {
+    let source = r#"
+        print math.sqrt(16);
+        print math.PI > 3;
+        print string.upper("abc");
+        print string.format("[{:>3}]", 7);
+        print json.stringify((1, 2), nil);
+        print types.type(rand.random);
+        print regex.split(",\s*", "a, b,c");
+        print clock() > 0;
+    "#;
+    assert_eq!(output(source), "4\ntrue\nABC\n[  7]\n[1,2]\nfunction\n(\"a\", \"b\", \"c\")\ntrue\n");
+}
+
+#[test]
+fn module_members_are_also_globals() {
+    let source = r#"
+        print len("abc");
+        print type(1);
+        print sqrt == math.sqrt;
+        print jsonStringify(jsonParse("[1]"), nil);
+        print split("a,b", ",");
+    "#;
+    assert_eq!(output(source), "3\nnumber\ntrue\n[1]\n(\"a\", \"b\")\n");
+}
+
+#[test]
+fn module_only_members_are_not_globals() {
+    for name in ["parse", "stringify", "splitRegex"] {
+        assert_eq!(runtime_error(&format!("print {};", name)), format!("Undefined variable '{}'", name));
+    }
+    assert_eq!(runtime_error("print json.jsonParse;"), "Undefined member 'jsonParse' in module json.");
+}
+
+#[test]
+fn scripts_can_use_native_names_for_their_own_variables() {
+    let source = "
+        var len = 3;
+        fun type(x) { return \"mine\"; }
+        print len;
+        print type(1);
+        print string.len(\"abcd\");
+    ";
+    assert_eq!(output(source), "3\nmine\n4\n");
+}
+
+#[test]
+fn modules_are_values() {
+    let source = "
+        print math;
+        print types.type(math);
+        var m = math;
+        print m.floor(2.5);
+        print m == math;
+        print math == string;
+    ";
+    assert_eq!(output(source), "<module math>\nmodule\n2\ntrue\nfalse\n");
+}
+
+#[test]
+fn unknown_members_are_runtime_errors() {
+    assert_eq!(runtime_error("print math.nope;"), "Undefined member 'nope' in module math.");
+}
+
+#[test]
+fn scripts_can_shadow_module_names() {
+    let source = "
+        var math = 1;
+        print math;
+        print string.len(\"ab\");
+    ";
+    assert_eq!(output(source), "1\n2\n");
+}
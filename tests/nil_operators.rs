@@ -0,0 +1,65 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn coalesce_evaluates_the_right_side_only_for_nil() {
+    let source = "
+        fun loud() { print \"evaluated\"; return 2; }
+        print nil ?? 1;
+        print false ?? loud();
+        print 0 ?? loud();
+        print nil ?? loud();
+    ";
+    assert_eq!(output(source), "1\nfalse\n0\nevaluated\n2\n");
+}
+
+#[test]
+fn optional_get_and_call_on_nil_are_nil() {
+    let source = "
+        var a = nil;
+        print a?.b;
+        print a?.(1, 2);
+        enum Color { Red }
+        var c = Color;
+        print c?.Red;
+    ";
+    assert_eq!(output(source), "nil\nnil\nColor.Red\n");
+}
+
+#[test]
+fn a_nil_receiver_skips_the_rest_of_the_chain() {
+    let source = "
+        fun loud() { print \"evaluated\"; return 0; }
+        var a = nil;
+        print a?.b.c;
+        print a?.b.c(loud());
+        print a?.b[loud()].c;
+        print a?.(loud()).d;
+        fun f() { return a?.b.c(); }
+        print f();
+    ";
+    assert_eq!(output(source), "nil\nnil\nnil\nnil\nnil\n");
+}
+
+#[test]
+fn nil_in_the_middle_of_a_chain_is_still_an_error() {
+    let source = "
+        fun nothing() { return nil; }
+        enum E { A }
+        fun get() { return E; }
+        print get?.().A;
+        print nothing?.().A;
+        print nothing().A;
+    ";
+    assert_eq!(runtime_error(source), "Only enums and modules have properties.");
+}
+
+#[test]
+fn grouping_ends_a_chain() {
+    let source = "
+        var a = nil;
+        print (a?.b).c;
+    ";
+    assert_eq!(runtime_error(source), "Only enums and modules have properties.");
+}
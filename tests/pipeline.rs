@@ -0,0 +1,56 @@
+mod common;
+
+use common::{output, run, run_command, runtime_error};
+
+#[test]
+fn the_left_value_becomes_the_first_argument() {
+    let source = r#"
+        fun double(x) { return x * 2; }
+        fun add(a, b) { return a + b; }
+        print 3 |> double;
+        print 3 |> add(4);
+        print 2 |> pow(3);
+        print "abc" |> upper;
+    "#;
+    assert_eq!(output(source), "6\n7\n8\nABC\n");
+}
+
+#[test]
+fn pipelines_chain_left_to_right_below_other_operators() {
+    let source = r#"
+        fun double(x) { return x * 2; }
+        fun show(x) { print x; return x; }
+        print 3 |> double |> double;
+        print 1 + 2 |> double;
+        var result = 1 |> show |> double |> show;
+    "#;
+    assert_eq!(output(source), "12\n6\n1\n2\n");
+}
+
+#[test]
+fn arity_is_checked_with_the_piped_argument_included() {
+    let source = "fun add(a, b) { return a + b; } print 1 |> add;";
+    assert_eq!(runtime_error(source), "Expected 2 arguments but got 1.");
+    let source = "fun add(a, b) { return a + b; } print 1 |> add(2, 3);";
+    assert_eq!(runtime_error(source), "Expected 2 arguments but got 3.");
+    assert_eq!(runtime_error("fun f() {} print 1 |> f;"), "Expected 0 arguments but got 1.");
+}
+
+#[test]
+fn only_callables_can_be_piped_into() {
+    assert_eq!(runtime_error("print 1 |> 2;"), "Can only call functions and classes.");
+
+    let run = run("print 1 |> ;");
+    assert_eq!(run.status, 65);
+    assert_eq!(run.stderr, "[line 1] Error at ';': Expect expression.\n");
+}
+
+#[test]
+fn parse_prints_the_desugared_call() {
+    let parse = |source: &str| run_command("parse", source).stdout;
+    assert_eq!(parse("x |> f"), "(|> f x)\n");
+    assert_eq!(parse("x |> f(y) |> g"), "(|> g (|> f x y))\n");
+    assert_eq!(parse("1 + 2 |> m.f(3)"), "(|> (. m f) (+ 1.0 2.0) 3.0)\n");
+    assert_eq!(parse("x |> f?.(y)"), "(?.|> f x y)\n");
+    assert_eq!(parse("f(x, y)"), "(function x y)\n");
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use std::time::{Duration, Instant};
+use common::{output, run_with, runtime_error};
+
+#[test]
+fn exec_returns_output_and_status() {
+    let source = r#"
+        var result = exec("sh", ("-c", "echo out; echo err >&2; exit 3"));
+        print result["stdout"];
+        print result["stderr"];
+        print result["status"];
+        print exec("cat", nil, (("stdin", "fed in"),))["stdout"];
+        print exec("pwd", nil, (("cwd", "/"),))["stdout"];
+    "#;
+    assert_eq!(output(source), "out\n\nerr\n\n3\nfed in\n/\n\n");
+}
+
+#[test]
+fn exec_takes_two_or_three_arguments() {
+    assert_eq!(runtime_error(r#"exec("true");"#), "Expected at least 2 arguments but got 1.");
+    assert_eq!(
+        runtime_error(r#"exec("true", nil, nil, nil);"#),
+        "exec: expected at most 3 arguments but got 4."
+    );
+}
+
+#[test]
+fn exec_does_not_wait_for_background_processes_holding_its_pipes() {
+    let start = Instant::now();
+    let source = r#"print exec("sh", ("-c", "sleep 5 & echo started"))["stdout"];"#;
+    assert_eq!(output(source), "started\n\n");
+    assert!(start.elapsed() < Duration::from_secs(4));
+}
+
+#[test]
+fn exec_kills_commands_that_time_out() {
+    assert_eq!(
+        runtime_error(r#"exec("sleep", ("5",), (("timeout", 0.2),));"#),
+        "exec: sleep timed out after 0.2s."
+    );
+}
+
+#[test]
+fn huge_timeouts_are_checked_instead_of_overflowing() {
+    assert_eq!(
+        runtime_error(r#"exec("true", nil, (("timeout", 100000000000000000000),));"#),
+        "exec: timeout must be a positive number of seconds."
+    );
+    let source = r#"print exec("true", nil, (("timeout", 10000000000000000000),))["status"];"#;
+    assert_eq!(output(source), "0\n");
+}
+
+#[test]
+fn the_sandbox_blocks_natives_that_reach_outside_the_script() {
+    let path = std::env::temp_dir().join(format!("lox-sandbox-{}", std::process::id()));
+    let path = path.to_str().unwrap();
+    let calls = [
+        (r#"exec("true", nil);"#.to_owned(), "exec"),
+        (r#"getenv("HOME");"#.to_owned(), "getenv"),
+        (format!(r#"writeFile("{}", "x");"#, path), "writeFile"),
+        (format!(r#"appendFile("{}", "x");"#, path), "appendFile"),
+        (format!(r#"remove("{}");"#, path), "remove"),
+    ];
+    for (call, function) in calls {
+        let run = run_with(&["--sandbox"], &call, &[]);
+        assert_eq!(run.status, 70);
+        assert_eq!(run.stderr.lines().next().unwrap(), format!("{}: not available in the sandbox.", function));
+    }
+    assert!(!std::path::Path::new(path).exists());
+}
+
+#[test]
+fn sandboxed_scripts_can_still_read_files_and_exit() {
+    let source = r#"
+        print exists("/");
+        print len(readFile("/dev/null"));
+        exit(3);
+    "#;
+    let run = run_with(&["--sandbox"], source, &[]);
+    assert_eq!((run.stdout.as_str(), run.status), ("true\n0\n", 3));
+}
+
+#[test]
+fn exit_ends_the_script_with_its_code() {
+    let run = run_with(&[], "print 1; exit(3); print 2;", &[]);
+    assert_eq!((run.stdout.as_str(), run.status), ("1\n", 3));
+
+    let run = run_with(&[], "exit(0); print 2;", &[]);
+    assert_eq!((run.stdout.as_str(), run.status), ("", 0));
+}
+
+#[test]
+fn exit_runs_deferred_statements_on_the_way_out() {
+    let source = "
+        defer print \"script\";
+        fun leave() {
+            defer print \"function\";
+            {
+                defer print \"block\";
+                exit(4);
+            }
+        }
+        leave();
+        print \"unreachable\";
+    ";
+    let run = run_with(&[], source, &[]);
+    assert_eq!((run.stdout.as_str(), run.status), ("block\nfunction\nscript\n", 4));
+}
+
+#[test]
+fn exit_codes_must_fit_in_a_byte() {
+    assert_eq!(runtime_error("exit(256);"), "exit: code must be between 0 and 255.");
+    assert_eq!(runtime_error("exit(-1);"), "exit: code must be between 0 and 255.");
+    assert_eq!(runtime_error("exit(1.5);"), "exit: code must be an integer.");
+}
+
+#[test]
+fn args_are_the_arguments_after_the_script() {
+    let run = run_with(&[], "print args();", &["one", "two words", "--flag"]);
+    assert_eq!((run.stdout.as_str(), run.status), ("(\"one\", \"two words\", \"--flag\")\n", 0));
+    assert_eq!(output("print args();"), "()\n");
+}
+
+#[test]
+fn getenv_reads_the_environment() {
+    assert_eq!(output("print getenv(\"PATH\") != nil;"), "true\n");
+    assert_eq!(output("print getenv(\"LOX_TEST_UNSET_VARIABLE\");"), "nil\n");
+    assert_eq!(
+        runtime_error("getenv(\"A=B\");"),
+        "getenv: name must not be empty or contain '=' or NUL."
+    );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{output, run_with, runtime_error};
+
+const SAMPLE: &str = "
+    print random();
+    print randomInt(1, 1000000);
+    print shuffle((1, 2, 3, 4, 5, 6, 7, 8));
+    print choice((\"a\", \"b\", \"c\", \"d\"));
+";
+
+fn output_seeded(seed: &str, source: &str) -> String {
+    let run = run_with(&[&format!("--seed={}", seed)], source, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    run.stdout
+}
+
+#[test]
+fn the_seed_flag_repeats_the_sequence_every_run() {
+    let first = output_seeded("42", SAMPLE);
+    assert_eq!(output_seeded("42", SAMPLE), first);
+    assert_ne!(output_seeded("43", SAMPLE), first);
+    assert_eq!(output_seeded("-1", SAMPLE), output_seeded("-1", SAMPLE));
+}
+
+#[test]
+fn seeding_from_the_script_repeats_the_sequence() {
+    let source = format!("seed(42); {} seed(42); {}", SAMPLE, SAMPLE);
+    let run = output(&source);
+    let lines: Vec<&str> = run.lines().collect();
+    assert_eq!(lines[..4], lines[4..]);
+    assert_eq!(output(&format!("seed(42); {}", SAMPLE)), output_seeded("42", SAMPLE));
+}
+
+#[test]
+fn unseeded_runs_differ() {
+    let source = "print randomInt(0, 1000000000000);";
+    assert_ne!(output(source), output(source));
+}
+
+#[test]
+fn random_numbers_stay_in_range() {
+    let source = "
+        var inRange = true;
+        var sixes = 0;
+        for (var i = 0; i < 1000; i = i + 1) {
+            var r = random();
+            if (r < 0 or r >= 1) inRange = false;
+            var die = randomInt(1, 6);
+            if (!(die in 1..=6) or floor(die) != die) inRange = false;
+            if (die == 6) sixes = sixes + 1;
+            if (randomInt(-3, -3) != -3) inRange = false;
+        }
+        print inRange;
+        print sixes > 100 and sixes < 250;
+    ";
+    assert_eq!(output_seeded("7", source), "true\ntrue\n");
+}
+
+#[test]
+fn shuffle_keeps_every_element() {
+    let source = "
+        var shuffled = shuffle((1, 2, 3, 4, 5));
+        var total = 0;
+        for (var n in shuffled) total = total + n;
+        print len(shuffled);
+        print total;
+        print shuffle(());
+    ";
+    assert_eq!(output_seeded("1", source), "5\n15\n()\n");
+}
+
+#[test]
+fn bad_arguments_are_runtime_errors() {
+    assert_eq!(runtime_error("randomInt(2, 1);"), "randomInt: low must not be greater than high.");
+    assert_eq!(runtime_error("choice(());"), "choice: can't choose from an empty tuple.");
+    assert_eq!(runtime_error("shuffle(1);"), "shuffle: argument must be a tuple.");
+}
+
+#[test]
+fn the_seed_flag_must_be_an_integer() {
+    let run = run_with(&["--seed=abc"], "print 1;", &[]);
+    assert_eq!(run.status, 64);
+    assert_eq!(run.stderr, "--seed: 'abc' is not an integer\n");
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn ranges_iterate_count_and_contain() {
+    let source = "
+        for (var i in 1..4) print i;
+        for (var i in 3..=3) print i;
+        for (var i in 3..1) print i;
+        print len(0..10);
+        print len(0..=10);
+        print len(5..0);
+        print 3 in 1..4;
+        print 4 in 1..4;
+        print 4 in 1..=4;
+        print 1.5 in 1..4;
+    ";
+    assert_eq!(output(source), "1\n2\n3\n3\n10\n11\n0\ntrue\nfalse\ntrue\nfalse\n");
+}
+
+#[test]
+fn ranges_slice_strings_tuples_and_bytes() {
+    let source = r#"
+        print "héllo"[1..3];
+        print (1, 2, 3, 4)[1..=2];
+        print b"abc"[0..0];
+    "#;
+    assert_eq!(output(source), "él\n(2, 3)\nb\"\"\n");
+}
+
+#[test]
+fn slices_outside_the_sequence_are_runtime_errors() {
+    assert_eq!(runtime_error("print \"abc\"[1..4];"), "Slice 1..4 out of range for length 3.");
+    assert_eq!(runtime_error("print \"abc\"[0..=9007199254740992];"), "Slice 0..=9007199254740992 out of range for length 3.");
+}
+
+#[test]
+fn reversed_slices_are_runtime_errors() {
+    assert_eq!(runtime_error("print \"abc\"[3..2];"), "Slice 3..2 out of range for length 3.");
+    assert_eq!(runtime_error("print \"abc\"[3..=2];"), "Slice 3..=2 out of range for length 3.");
+    assert_eq!(runtime_error("print (1, 2)[1..=0];"), "Slice 1..=0 out of range for length 2.");
+    assert_eq!(output("print \"abc\"[3..3] == \"\";"), "true\n");
+}
+
+#[test]
+fn huge_bounds_are_runtime_errors() {
+    let message = "Range bounds must be between -2^53 and 2^53.";
+    assert_eq!(runtime_error("for (var i in 9223372036854775806..=9223372036854775807) print i;"), message);
+    assert_eq!(runtime_error("print \"abc\"[0..=9223372036854775807];"), message);
+    assert_eq!(runtime_error("print len(0..=9223372036854775807);"), message);
+    assert_eq!(runtime_error("print len(-100000000000000000000..0);"), message);
+}
+
+#[test]
+fn the_largest_allowed_bounds_work() {
+    let source = "
+        print len(0..9007199254740992);
+        for (var i in 9007199254740990..=9007199254740992) print i;
+    ";
+    assert_eq!(output(source), "9007199254740992\n9007199254740990\n9007199254740991\n9007199254740992\n");
+}
+
+#[test]
+fn bounds_must_be_integers() {
+    assert_eq!(runtime_error("print 1..2.5;"), "Range bounds must be integers.");
+    assert_eq!(runtime_error("print 1..\"2\";"), "Range bounds must be integers.");
+}
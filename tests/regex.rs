@@ -0,0 +1,46 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn match_returns_the_groups_or_nil() {
+    let source = r#"
+        print match("(\w+)@(\w+)?", "me@");
+        print match("\d", "abc");
+    "#;
+    assert_eq!(output(source), "(\"me@\", \"me\", nil)\nnil\n");
+}
+
+#[test]
+fn find_replace_and_split() {
+    let source = r#"
+        print findAll("\d+", "a1b22c333");
+        print replaceAll("\s+", "a  b   c", " ");
+        print regex.split(",\s*", "a, b,c");
+        print split("a, b", ", ");
+    "#;
+    let expected = r#"("1", "22", "333")
+a b c
+("a", "b", "c")
+("a", "b")
+"#;
+    assert_eq!(output(source), expected);
+}
+
+#[test]
+fn many_patterns_still_match() {
+    let source = r#"
+        var found = 0;
+        for (var i = 0; i < 200; i = i + 1) {
+            if (match(str(i) + "$", "x" + str(i)) != nil) found = found + 1;
+        }
+        print found;
+    "#;
+    assert_eq!(output(source), "200\n");
+}
+
+#[test]
+fn invalid_patterns_are_runtime_errors() {
+    assert!(runtime_error(r#"match("(", "x");"#).starts_with("match: invalid pattern"));
+    assert!(runtime_error(r#"regex.split("[", "x");"#).starts_with("split: invalid pattern"));
+}
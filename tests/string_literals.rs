@@ -0,0 +1,59 @@
+mod common;
+
+use common::{output, run};
+
+#[test]
+fn raw_strings_keep_backslashes_and_quotes() {
+    let source = r####"
+        print r"C:\path\n";
+        print r#"say "hi""#;
+        print r##"a "# inside"##;
+        print r"";
+    "####;
+    assert_eq!(output(source), "C:\\path\\n\nsay \"hi\"\na \"# inside\n\n");
+}
+
+#[test]
+fn r_on_its_own_is_still_an_identifier() {
+    let source = r#"
+        var r = 1;
+        var rx = 2;
+        print r + rx;
+    "#;
+    assert_eq!(output(source), "3\n");
+}
+
+#[test]
+fn triple_quoted_strings_strip_common_indentation() {
+    let source = "
+        print \"\"\"
+            first
+              indented
+            \"quoted\"
+
+            last
+        \"\"\";
+        print \"\"\"one line\"\"\";
+    ";
+    assert_eq!(output(source), "first\n  indented\n\"quoted\"\n\nlast\none line\n");
+}
+
+#[test]
+fn multi_line_literals_keep_line_numbers_right() {
+    let source = "print \"\"\"\n    a\n    b\n\"\"\";\nprint r\"x\ny\";\nprint nil + 1;";
+    let result = run(source);
+    assert_eq!(result.stdout, "a\nb\nx\ny\n");
+    assert_eq!(result.stderr, "Operands must be two numbers or two strings.\n[line 7]\n");
+
+    let result = run("var s = \"\"\"\n\n\"\"\";\nprint -s;");
+    assert_eq!(result.stderr, "Operand must be a number.\n[line 4]\n");
+}
+
+#[test]
+fn unterminated_literals_are_scan_errors() {
+    for source in ["print \"\"\"never closed\"\";", "print r#\"never closed\";", "print r\"\nnever closed"] {
+        let run = run(source);
+        assert_eq!(run.status, 65, "{}", source);
+        assert!(run.stderr.contains("Error: Unterminated string."), "{}", run.stderr);
+    }
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn lengths_and_positions_count_code_points() {
+    let source = r#"
+        print len("héllo");
+        print len("a😀b");
+        print len("");
+        print substring("héllo", 1, 4);
+        print substring("a😀b", 1, 2);
+        print substring("abc", 3, 3) == "";
+        print indexOf("héllo", "l");
+        print indexOf("😀😀x", "x");
+        print indexOf("abc", "z");
+        print chars("h😀é");
+        print chars("");
+    "#;
+    assert_eq!(output(source), "5\n3\n0\néll\n😀\ntrue\n2\n2\n-1\n(\"h\", \"😀\", \"é\")\n()\n");
+}
+
+#[test]
+fn len_measures_every_value_with_a_length() {
+    let source = r#"
+        print len(b"ab\x00");
+        print len((1, 2));
+        print len(1..4);
+        print len(jsonParse("""{"a": 1}"""));
+    "#;
+    assert_eq!(output(source), "3\n2\n3\n1\n");
+}
+
+#[test]
+fn substring_rejects_bounds_outside_the_string() {
+    assert_eq!(runtime_error("substring(\"héllo\", 2, 6);"), "substring: 2..6 is out of range for length 5.");
+    assert_eq!(runtime_error("substring(\"abc\", -1, 2);"), "substring: -1..2 is out of range for length 3.");
+    assert_eq!(runtime_error("substring(\"abc\", 2, 1);"), "substring: 2..1 is out of range for length 3.");
+}
+
+#[test]
+fn split_and_join() {
+    let source = r#"
+        print split("a,b,,c", ",");
+        print split("é😀é😀", "😀");
+        print join(("a", "b", "c"), ", ");
+        print join((), "-") == "";
+    "#;
+    assert_eq!(output(source), "(\"a\", \"b\", \"\", \"c\")\n(\"é\", \"é\", \"\")\na, b, c\ntrue\n");
+}
+
+#[test]
+fn trim_upper_lower_and_replace() {
+    let source = r#"
+        print "[" + trim("  a b  ") + "]";
+        print upper("straße");
+        print lower("ÉCOLE");
+        print replace("a-b-c", "-", "+");
+        print replace("aaa", "aa", "b");
+    "#;
+    assert_eq!(output(source), "[a b]\nSTRASSE\nécole\na+b+c\nba\n");
+}
+
+#[test]
+fn starts_with_and_ends_with() {
+    let source = r#"
+        print startsWith("héllo", "hé");
+        print startsWith("héllo", "é");
+        print endsWith("héllo", "llo");
+        print endsWith("héllo", "");
+    "#;
+    assert_eq!(output(source), "true\nfalse\ntrue\ntrue\n");
+}
+
+#[test]
+fn empty_separators_and_search_strings_are_runtime_errors() {
+    assert_eq!(runtime_error("split(\"abc\", \"\");"), "split: separator must not be empty.");
+    assert_eq!(runtime_error("replace(\"abc\", \"\", \"x\");"), "replace: search string must not be empty.");
+}
+
+#[test]
+fn bad_argument_types_name_the_function() {
+    let cases = [
+        ("len(1);", "len: argument must be a string, byte string, tuple, range or map."),
+        ("substring(1, 0, 1);", "substring: string must be a string."),
+        ("substring(\"abc\", \"0\", 1);", "substring: start must be an integer."),
+        ("substring(\"abc\", 0, 1.5);", "substring: end must be an integer."),
+        ("indexOf(\"abc\", nil);", "indexOf: search string must be a string."),
+        ("split(nil, \",\");", "split: string must be a string."),
+        ("join(\"ab\", \",\");", "join: first argument must be a tuple."),
+        ("join((\"a\", 1), \",\");", "join: every element must be a string."),
+        ("join((\"a\",), 1);", "join: separator must be a string."),
+        ("trim(1);", "trim: argument must be a string."),
+        ("upper(true);", "upper: argument must be a string."),
+        ("lower(nil);", "lower: argument must be a string."),
+        ("replace(\"a\", \"a\", 1);", "replace: replacement must be a string."),
+        ("startsWith(\"a\", 1);", "startsWith: prefix must be a string."),
+        ("endsWith(1, \"a\");", "endsWith: string must be a string."),
+        ("repeat(1, 2);", "repeat: string must be a string."),
+        ("chars((\"a\",));", "chars: argument must be a string."),
+    ];
+    for (source, message) in cases {
+        assert_eq!(runtime_error(source), message, "for {}", source);
+    }
+}
+
+#[test]
+fn repeat_repeats() {
+    let source = r#"
+        print repeat("ab", 3);
+        print repeat("ab", 0) == "";
+        print repeat("", 100000000000000000000) == "";
+    "#;
+    assert_eq!(output(source), "ababab\ntrue\ntrue\n");
+}
+
+#[test]
+fn repeat_rejects_bad_counts() {
+    assert_eq!(runtime_error("repeat(\"ab\", -1);"), "repeat: count must not be negative.");
+    assert_eq!(runtime_error("repeat(\"ab\", 1.5);"), "repeat: count must be an integer.");
+    assert_eq!(runtime_error("repeat(\"ab\", nil);"), "repeat: count must be an integer.");
+    assert_eq!(runtime_error("repeat(\"ab\", 1 / 0);"), "repeat: count must be an integer.");
+}
+
+#[test]
+fn repeat_rejects_results_too_long_to_build() {
+    let message = "repeat: the result would be longer than 268435456 bytes.";
+    assert_eq!(runtime_error("repeat(\"ab\", 100000000000000000000000000000);"), message);
+    assert_eq!(runtime_error("repeat(\"ab\", 9223372036854775807);"), message);
+    assert_eq!(runtime_error("repeat(\"ab\", 134217729);"), message);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn self_recursion_in_tail_position_runs_a_million_deep() {
+    let source = "
+        fun count(n, acc) {
+            if (n == 0) return acc;
+            return count(n - 1, acc + 1);
+        }
+        print count(1000000, 0);
+    ";
+    assert_eq!(output(source), "1000000\n");
+}
+
+#[test]
+fn mutual_recursion_in_tail_position_runs_a_million_deep() {
+    let source = "
+        fun isEven(n) {
+            if (n == 0) return true;
+            return isOdd(n - 1);
+        }
+        fun isOdd(n) {
+            if (n == 0) return false;
+            return isEven(n - 1);
+        }
+        print isEven(1000000);
+        print isOdd(999999);
+    ";
+    assert_eq!(output(source), "true\ntrue\n");
+}
+
+#[test]
+fn tail_calls_see_their_own_closures() {
+    let source = "
+        fun makeAdder(n) {
+            fun add(x) { return x + n; }
+            return add;
+        }
+        fun apply(f, x) { return f(x); }
+        print apply(makeAdder(2), 40);
+    ";
+    assert_eq!(output(source), "42\n");
+}
+
+#[test]
+fn recursion_outside_tail_position_runs_as_deep_as_before_tail_calls() {
+    let source = "
+        fun depth(n) {
+            if (n == 0) return 0;
+            return 1 + depth(n - 1);
+        }
+        print depth(954);
+    ";
+    assert_eq!(output(source), "954\n");
+}
+
+#[test]
+fn a_call_returned_from_the_script_runs_before_its_fibers_and_deferred_statements() {
+    let source = "
+        fun worker() { print \"worker\"; }
+        fun main() {
+            spawn(worker);
+            print \"main\";
+        }
+        return main();
+        print \"unreachable\";
+    ";
+    assert_eq!(output(source), "main\nworker\n");
+
+    let source = "
+        defer print \"deferred\";
+        fun main() { print \"main\"; }
+        return main();
+    ";
+    assert_eq!(output(source), "main\ndeferred\n");
+}
+
+#[test]
+fn errors_in_a_call_returned_from_the_script_are_reported() {
+    assert_eq!(runtime_error("fun f() { return nil + 1; } return f();"), "Operands must be two numbers or two strings.");
+}
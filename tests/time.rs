@@ -0,0 +1,90 @@
+mod common;
+
+use common::{run_with, runtime_error};
+
+// Runs the source with the clock pinned to `time`, expecting it to succeed.
+fn output_at(time: &str, source: &str) -> String {
+    let run = run_with(&[&format!("--time={}", time)], source, &[]);
+    assert_eq!(run.status, 0, "stderr: {}", run.stderr);
+    run.stdout
+}
+
+#[test]
+fn the_clock_can_be_pinned() {
+    let source = r#"
+        print now();
+        print formatTime(now(), "%A %F %T", nil);
+        print formatTime(now() + duration("1d2h"), "%a %d %b %Y %H:%M", "+05:30");
+        print monotonic();
+    "#;
+    let expected = "1714564800\nWednesday 2024-05-01 12:00:00\nThu 02 May 2024 19:30\n0\n";
+    assert_eq!(output_at("2024-05-01T12:00:00Z", source), expected);
+}
+
+#[test]
+fn offsets_need_two_digit_hours_and_minutes() {
+    let source = r#"
+        print formatTime(0, "%T %z", "+05:30");
+        print formatTime(0, "%T %z", "-0800");
+        print formatTime(0, "%T %z", "+02");
+        print formatTime(0, "%T %z", "Z");
+        print parseTime("2024-05-01T12:00+01:00") == parseTime("2024-05-01T11:00Z");
+    "#;
+    let expected = "05:30:00 +0530\n16:00:00 -0800\n02:00:00 +0200\n00:00:00 +0000\ntrue\n";
+    assert_eq!(output_at("2024-05-01T12:00:00Z", source), expected);
+
+    for offset in ["+0:30", "+5", "+5:30", "+05:3", "+0530:", "+05-30", "05:30", "+24:00", "+05:60"] {
+        assert_eq!(
+            runtime_error(&format!(r#"formatTime(0, "%T", "{}");"#, offset)),
+            format!("formatTime: invalid UTC offset '{}'.", offset)
+        );
+    }
+    assert_eq!(
+        runtime_error(r#"parseTime("2024-05-01T12:00+0:30");"#),
+        "parseTime: '2024-05-01T12:00+0:30' is not an ISO-8601 date or timestamp."
+    );
+}
+
+#[test]
+fn timestamps_far_from_1970_are_runtime_errors() {
+    let message = "formatTime: timestamp must be a number of seconds within 8.64e12 of 1970.";
+    assert_eq!(runtime_error(r#"formatTime(1000000000000000000000, "%F", nil);"#), message);
+    assert_eq!(runtime_error(r#"formatTime(-8640000000001, "%F", nil);"#), message);
+    assert_eq!(runtime_error(r#"formatTime(0/0, "%F", nil);"#), message);
+    assert_eq!(runtime_error(r#"formatTime(1/0, "%F", nil);"#), message);
+}
+
+#[test]
+fn the_ends_of_the_range_can_be_formatted() {
+    let source = r#"
+        print formatTime(8640000000000, "%F %T", "+23:59");
+        print formatTime(-8640000000000, "%F %T", "-23:59");
+    "#;
+    assert_eq!(output_at("1970-01-01", source), "275760-09-13 23:59:00\n-271821-04-19 00:01:00\n");
+}
+
+#[test]
+fn durations_format_back_into_units() {
+    let source = r#"
+        print formatDuration(5400);
+        print formatDuration(duration("2d1s"));
+        print formatDuration(-90.5);
+        print formatDuration(0.1 + 0.2);
+        print formatDuration(0);
+        print formatDuration(duration("1h30m")) == "1h30m";
+    "#;
+    assert_eq!(output_at("1970-01-01", source), "1h30m\n2d1s\n-1m30.5s\n0.3s\n0s\ntrue\n");
+}
+
+#[test]
+fn only_finite_durations_can_be_formatted() {
+    assert_eq!(runtime_error("formatDuration(1/0);"), "formatDuration: argument must be finite.");
+    assert_eq!(runtime_error("formatDuration(0/0);"), "formatDuration: argument must be finite.");
+    assert_eq!(runtime_error("formatDuration(\"1h\");"), "formatDuration: argument must be a number.");
+}
+
+#[test]
+fn clock_is_a_global_only() {
+    assert_eq!(output_at("2024-05-01T12:00:00Z", "print clock() == now();"), "true\n");
+    assert_eq!(runtime_error("time.clock();"), "Undefined member 'clock' in module time.");
+}
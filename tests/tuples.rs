@@ -0,0 +1,61 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn tuples_print_index_and_compare() {
+    let source = r#"
+        var t = (1, "two", nil);
+        print t;
+        print (1,);
+        print ();
+        print t[1];
+        print len(t);
+        print (1, 2) == (1, 2);
+        print (1, 2) == (2, 1);
+        for (var e in (1, 2)) print e;
+    "#;
+    assert_eq!(output(source), "(1, \"two\", nil)\n(1,)\n()\ntwo\n3\ntrue\nfalse\n1\n2\n");
+}
+
+#[test]
+fn functions_return_several_values_as_a_tuple() {
+    let source = "
+        fun divide(a, b) { return floor(a / b), a - floor(a / b) * b; }
+        print divide(7, 2);
+        var (quotient, remainder) = divide(7, 2);
+        print quotient;
+        print remainder;
+    ";
+    assert_eq!(output(source), "(3, 1)\n3\n1\n");
+}
+
+#[test]
+fn destructuring_binds_each_element() {
+    let source = "
+        var (a, b, c) = (1, (2, 3), \"x\");
+        print a;
+        print b;
+        print c;
+        var (only) = (4,);
+        print only;
+    ";
+    assert_eq!(output(source), "1\n(2, 3)\nx\n4\n");
+}
+
+#[test]
+fn destructuring_needs_a_tuple_of_the_right_size() {
+    assert_eq!(runtime_error("var (a, b) = (1, 2, 3);"), "Expected 2 values to unpack but got 3.");
+    assert_eq!(runtime_error("var (a, b) = (1,);"), "Expected 2 values to unpack but got 1.");
+    assert_eq!(
+        runtime_error("fun f() { return 1, 2; } var (a, b, c) = f();"),
+        "Expected 3 values to unpack but got 2."
+    );
+    assert_eq!(runtime_error("var (a, b) = 1;"), "Can only destructure a tuple.");
+}
+
+#[test]
+fn indexing_past_the_end_is_a_runtime_error() {
+    assert_eq!(runtime_error("print (1, 2)[2];"), "Index 2 out of range for length 2.");
+    assert_eq!(runtime_error("print (1, 2)[-1];"), "Index -1 out of range for length 2.");
+}
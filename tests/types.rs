@@ -0,0 +1,70 @@
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn type_names_each_kind_of_value() {
+    let source = r#"
+        fun f() {}
+        print type(nil);
+        print type(true);
+        print type(1.5);
+        print type("s");
+        print type(b"s");
+        print type(f);
+        print type(clock);
+        print type((1, 2));
+        print type(1..2);
+        print type(math);
+    "#;
+    assert_eq!(
+        output(source),
+        "nil\nbool\nnumber\nstring\nbytes\nfunction\nfunction\ntuple\nrange\nmodule\n"
+    );
+}
+
+#[test]
+fn str_matches_what_print_shows() {
+    let source = r#"
+        print str(3) + "!";
+        print str(2.5) + "!";
+        print str(nil) + "!";
+        print str(false) + "!";
+        print str((1, "a")) + "!";
+    "#;
+    assert_eq!(output(source), "3!\n2.5!\nnil!\nfalse!\n(1, \"a\")!\n");
+}
+
+#[test]
+fn num_parses_decimal_strings_and_gives_nil_otherwise() {
+    let source = r#"
+        print num("42") + 1;
+        print num("  -1.5e2 ");
+        print num(7);
+        print num("12abc");
+        print num("");
+        print num("inf");
+        print num("NaN");
+    "#;
+    assert_eq!(output(source), "43\n-150\n7\nnil\nnil\nnil\nnil\n");
+}
+
+#[test]
+fn bool_follows_lox_truthiness() {
+    let source = r#"
+        print bool(nil);
+        print bool(false);
+        print bool(0);
+        print bool("");
+        print bool(true);
+    "#;
+    assert_eq!(output(source), "false\nfalse\ntrue\ntrue\ntrue\n");
+}
+
+#[test]
+fn num_only_converts_strings_and_numbers() {
+    assert_eq!(runtime_error("num(true);"), "num: argument must be a string or number.");
+    assert_eq!(runtime_error("num(nil);"), "num: argument must be a string or number.");
+    assert_eq!(runtime_error("str();"), "Expected 1 arguments but got 0.");
+    assert_eq!(runtime_error("type(1, 2);"), "Expected 1 arguments but got 2.");
+}